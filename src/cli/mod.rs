@@ -8,15 +8,73 @@ pub struct Cli {
 #[derive(Debug)]
 pub enum Commands {
     New { project: String },
-    Build,
-    Run,
-    Check,
+    Build(BuildOptions),
+    Run(BuildOptions),
+    Check(BuildOptions),
     Install,
     Update,
     Publish,
     Clean,
 }
 
+// Opciones de compilación condicional (`@cfg`) comunes a build, run y check;
+// lo que va después de `--` son los argumentos del programa (solo en run)
+#[derive(Debug)]
+pub struct BuildOptions {
+    pub features: Vec<String>,
+    pub default_features: bool,
+    pub profile: String,
+    pub program_args: Vec<String>,
+}
+
+impl BuildOptions {
+    fn parse(mut args: impl Iterator<Item = String>) -> KujavResult<Self> {
+        let mut options = Self {
+            features: Vec::new(),
+            default_features: true,
+            profile: "debug".to_string(),
+            program_args: Vec::new(),
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--features" => {
+                    let list = args
+                        .next()
+                        .ok_or_else(|| KujavError::io("usage: --features <a,b,...>"))?;
+                    options.features.extend(
+                        list.split(',')
+                            .map(str::trim)
+                            .filter(|f| !f.is_empty())
+                            .map(String::from),
+                    );
+                }
+                "--" => {
+                    options.program_args.extend(args.by_ref());
+                    break;
+                }
+                "--no-default-features" => options.default_features = false,
+                "--release" => options.profile = "release".to_string(),
+                "--profile" => {
+                    options.profile = args
+                        .next()
+                        .ok_or_else(|| KujavError::io("usage: --profile <name>"))?;
+                }
+                other => return Err(KujavError::io(format!("unknown option '{other}'"))),
+            }
+        }
+        Ok(options)
+    }
+
+    fn without_program_args(self, command: &str) -> KujavResult<Self> {
+        if !self.program_args.is_empty() {
+            return Err(KujavError::io(format!(
+                "kujav {command} does not take program arguments (use kujav run -- <args>)"
+            )));
+        }
+        Ok(self)
+    }
+}
+
 impl Cli {
     pub fn parse() -> KujavResult<Self> {
         let mut args = std::env::args().skip(1);
@@ -27,9 +85,13 @@ impl Cli {
                     .ok_or_else(|| KujavError::io("usage: kujav new <project>"))?;
                 Commands::New { project }
             }
-            Some("build") => Commands::Build,
-            Some("run") => Commands::Run,
-            Some("check") => Commands::Check,
+            Some("build") => {
+                Commands::Build(BuildOptions::parse(args)?.without_program_args("build")?)
+            }
+            Some("run") => Commands::Run(BuildOptions::parse(args)?),
+            Some("check") => {
+                Commands::Check(BuildOptions::parse(args)?.without_program_args("check")?)
+            }
             Some("install") => Commands::Install,
             Some("update") => Commands::Update,
             Some("publish") => Commands::Publish,
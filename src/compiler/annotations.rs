@@ -0,0 +1,192 @@
+// src/compiler/annotations.rs
+use crate::compiler::java_symbols::JavaSymbols;
+use crate::parser::ast::{Annotation, AnnotationValue};
+use crate::reader::class_index::{ACC_ENUM, AnnotationMeta, ClassInfo};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Retention {
+    Source,
+    Class,   // RuntimeInvisible*Annotations
+    Runtime, // RuntimeVisible*Annotations
+}
+
+// Anotación validada contra su tipo del classpath, lista para escribir en el .class
+#[derive(Debug, Clone)]
+pub struct ResolvedAnnotation {
+    pub descriptor: String,
+    pub retention: Retention,
+    pub elements: Vec<(String, ElementValue)>,
+}
+
+// Un valor por cada `tag` de element_value (JVMS 4.7.16.1)
+#[derive(Debug, Clone)]
+pub enum ElementValue {
+    Const(u8, i32), // B C I S Z
+    String(String),
+    Enum { descriptor: String, name: String },
+    Class(String),
+    Annotation(ResolvedAnnotation),
+    Array(Vec<ElementValue>),
+}
+
+// `target` es la constante de ElementType donde aparece (METHOD, PARAMETER, FIELD, TYPE);
+// `None` para anotaciones anidadas como valor de otro elemento, que no dependen de @Target
+pub fn resolve_annotation(
+    ann: &Annotation,
+    java: &JavaSymbols,
+    target: Option<&str>,
+) -> Result<ResolvedAnnotation, String> {
+    let class = java.resolve(&ann.name).ok_or(format!(
+        "Anotación '@{}' no encontrada en el classpath",
+        ann.name
+    ))?;
+    if !class.is_annotation() {
+        return Err(format!("'{}' no es un tipo anotación", ann.name));
+    }
+    let meta = class.annotation.clone().unwrap_or(AnnotationMeta {
+        retention: "CLASS".into(),
+        targets: Vec::new(),
+    });
+    if let Some(target) = target
+        && !meta.targets.is_empty()
+        && !meta.targets.iter().any(|t| t == target)
+    {
+        return Err(format!(
+            "'@{}' no se puede usar en {} (solo en {})",
+            ann.name,
+            target,
+            meta.targets.join(", ")
+        ));
+    }
+
+    let mut elements = Vec::new();
+    for (key, value) in &ann.args {
+        if elements.iter().any(|(k, _)| k == key) {
+            return Err(format!("Elemento '{}' repetido en '@{}'", key, ann.name));
+        }
+        let element = class
+            .annotation_elements()
+            .find(|m| m.name == *key)
+            .ok_or(format!("'@{}' no tiene el elemento '{}'", ann.name, key))?;
+        // Los elementos son métodos sin parámetros: "()<descriptor>"
+        let descriptor = element.descriptor.trim_start_matches("()");
+        let v = resolve_value(value, descriptor, java)
+            .map_err(|e| format!("'@{}({} = ...)': {}", ann.name, key, e))?;
+        elements.push((key.clone(), v));
+    }
+    for method in class.annotation_elements() {
+        if !method.has_default && !elements.iter().any(|(k, _)| *k == method.name) {
+            return Err(format!(
+                "Falta el elemento obligatorio '{}' en '@{}'",
+                method.name, ann.name
+            ));
+        }
+    }
+
+    Ok(ResolvedAnnotation {
+        descriptor: format!("L{};", class.name),
+        retention: match meta.retention.as_str() {
+            "SOURCE" => Retention::Source,
+            "RUNTIME" => Retention::Runtime,
+            _ => Retention::Class,
+        },
+        elements,
+    })
+}
+
+fn resolve_value(
+    value: &AnnotationValue,
+    descriptor: &str,
+    java: &JavaSymbols,
+) -> Result<ElementValue, String> {
+    if let Some(elem_desc) = descriptor.strip_prefix('[') {
+        // Como en Java, un valor suelto equivale a un arreglo de un elemento
+        return match value {
+            AnnotationValue::Array(items) => Ok(ElementValue::Array(
+                items
+                    .iter()
+                    .map(|v| resolve_value(v, elem_desc, java))
+                    .collect::<Result<_, _>>()?,
+            )),
+            single => Ok(ElementValue::Array(vec![resolve_value(
+                single, elem_desc, java,
+            )?])),
+        };
+    }
+
+    match (descriptor, value) {
+        ("I", AnnotationValue::Number(n)) => Ok(ElementValue::Const(b'I', *n)),
+        ("S", AnnotationValue::Number(n)) if i16::try_from(*n).is_ok() => {
+            Ok(ElementValue::Const(b'S', *n))
+        }
+        ("B", AnnotationValue::Number(n)) if i8::try_from(*n).is_ok() => {
+            Ok(ElementValue::Const(b'B', *n))
+        }
+        ("S" | "B", AnnotationValue::Number(n)) => Err(format!(
+            "{} fuera del rango de {}",
+            n,
+            if descriptor == "S" { "short" } else { "byte" }
+        )),
+        ("Z", AnnotationValue::Boolean(b)) => Ok(ElementValue::Const(b'Z', *b as i32)),
+        ("C", AnnotationValue::String(s)) if s.chars().count() == 1 => {
+            let c = s.chars().next().unwrap();
+            u16::try_from(c as u32)
+                .map(|c| ElementValue::Const(b'C', c as i32))
+                .map_err(|_| format!("'{}' no cabe en un char", c))
+        }
+        ("Ljava/lang/String;", AnnotationValue::String(s)) => Ok(ElementValue::String(s.clone())),
+        ("Ljava/lang/Class;", AnnotationValue::Name(n)) => {
+            let class = java
+                .resolve(n)
+                .ok_or(format!("clase '{}' no encontrada", n))?;
+            Ok(ElementValue::Class(format!("L{};", class.name)))
+        }
+        ("J" | "F" | "D", _) => Err("elementos long/float/double aún no soportados".into()),
+        (_, AnnotationValue::Name(n)) if descriptor.starts_with('L') => {
+            let type_name = &descriptor[1..descriptor.len() - 1];
+            let enum_class = java.classes.get(type_name).ok_or(format!(
+                "enum '{}' no encontrado en el classpath",
+                type_name
+            ))?;
+            resolve_enum_constant(n, enum_class, java)
+        }
+        (_, AnnotationValue::Annotation(inner)) if descriptor.starts_with('L') => {
+            let nested = resolve_annotation(inner, java, None)?;
+            if nested.descriptor != descriptor {
+                return Err(format!("se esperaba una anotación {}", descriptor));
+            }
+            Ok(ElementValue::Annotation(nested))
+        }
+        _ => Err(format!("valor incompatible con el tipo {}", descriptor)),
+    }
+}
+
+// Acepta `HIGH`, `EventPriority.HIGH` u `org.bukkit.event.EventPriority.HIGH`
+fn resolve_enum_constant(
+    name: &str,
+    enum_class: &ClassInfo,
+    java: &JavaSymbols,
+) -> Result<ElementValue, String> {
+    let (owner, constant) = match name.rsplit_once('.') {
+        Some((owner, constant)) => (Some(owner), constant),
+        None => (None, name),
+    };
+    if let Some(owner) = owner
+        && java.resolve(owner).map(|c| &c.name) != Some(&enum_class.name)
+    {
+        return Err(format!(
+            "'{}' no es una constante de {}",
+            name, enum_class.name
+        ));
+    }
+    match enum_class.field(constant) {
+        Some(f) if f.access_flags & ACC_ENUM != 0 => Ok(ElementValue::Enum {
+            descriptor: format!("L{};", enum_class.name),
+            name: constant.to_string(),
+        }),
+        _ => Err(format!(
+            "'{}' no es una constante de {}",
+            name, enum_class.name
+        )),
+    }
+}
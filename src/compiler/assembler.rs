@@ -0,0 +1,789 @@
+// src/compiler/assembler.rs
+//
+// Ensamblador de los bloques `asm ... end`: una instrucción JVM por línea en
+// forma de texto, etiquetas `nombre:` para los saltos y locales de Kujav por
+// nombre. El análisis semántico valida el efecto de cada instrucción sobre la
+// pila; la generación de código resuelve las referencias en el ConstantPool.
+use crate::core::constant_pool::ConstantPool;
+use std::collections::HashMap;
+
+// Categoría de un valor en la pila de operandos
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VType {
+    Int, // También Bool, Byte, Short y Char
+    Long,
+    Float,
+    Double,
+    Ref,
+}
+
+impl VType {
+    fn from_char(c: char) -> VType {
+        match c {
+            'J' => VType::Long,
+            'F' => VType::Float,
+            'D' => VType::Double,
+            'L' | '[' | 'A' => VType::Ref,
+            _ => VType::Int,
+        }
+    }
+
+    // Descriptor de campo o de retorno; None para V
+    pub fn from_desc(desc: &str) -> Option<VType> {
+        desc.chars()
+            .next()
+            .filter(|c| *c != 'V')
+            .map(VType::from_char)
+    }
+
+    fn slots(self) -> usize {
+        if matches!(self, VType::Long | VType::Double) {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Operand {
+    None,
+    Int(i32),                       // bipush, sipush, ldc
+    Str(String),                    // ldc "texto"
+    Local(String),                  // xload/xstore nombre
+    Iinc(String, i32),              // iinc nombre n
+    Label(String),                  // saltos
+    Class(String),                  // new, checkcast, instanceof, anewarray
+    ArrayType(u8),                  // newarray int
+    Member(String, String, String), // (Clase, nombre, descriptor) de campos y métodos
+}
+
+#[derive(Debug, Clone)]
+pub struct Instr {
+    line: usize,
+    name: &'static str,
+    opcode: u8,
+    operand: Operand,
+}
+
+#[derive(Debug, Clone)]
+pub enum AsmItem {
+    Label(String),
+    Instr(Instr),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Plain,
+    Byte,
+    Short,
+    Ldc,
+    Local,
+    Iinc,
+    Jump,
+    Class,
+    NewArray,
+    Field,
+    Method,
+}
+
+const MAX_STACK: usize = u16::MAX as usize; // max_stack es un u16 en el atributo Code
+
+// (Mnemónico, opcode, operando, valores que consume de abajo arriba, valores que deja)
+// con I J F D A como categorías; los campos, los argumentos de los métodos y las
+// instrucciones de pila (dup, pop, swap) calculan su efecto aparte.
+#[rustfmt::skip]
+const TABLE: &[(&str, u8, Kind, &str, &str)] = &[
+    ("nop", 0x00, Kind::Plain, "", ""),
+    ("aconst_null", 0x01, Kind::Plain, "", "A"),
+    ("iconst_m1", 0x02, Kind::Plain, "", "I"), ("iconst_0", 0x03, Kind::Plain, "", "I"),
+    ("iconst_1", 0x04, Kind::Plain, "", "I"), ("iconst_2", 0x05, Kind::Plain, "", "I"),
+    ("iconst_3", 0x06, Kind::Plain, "", "I"), ("iconst_4", 0x07, Kind::Plain, "", "I"),
+    ("iconst_5", 0x08, Kind::Plain, "", "I"),
+    ("lconst_0", 0x09, Kind::Plain, "", "J"), ("lconst_1", 0x0A, Kind::Plain, "", "J"),
+    ("fconst_0", 0x0B, Kind::Plain, "", "F"), ("fconst_1", 0x0C, Kind::Plain, "", "F"),
+    ("fconst_2", 0x0D, Kind::Plain, "", "F"),
+    ("dconst_0", 0x0E, Kind::Plain, "", "D"), ("dconst_1", 0x0F, Kind::Plain, "", "D"),
+    ("bipush", 0x10, Kind::Byte, "", "I"), ("sipush", 0x11, Kind::Short, "", "I"),
+    ("ldc", 0x12, Kind::Ldc, "", ""),
+    ("iload", 0x15, Kind::Local, "", "I"), ("lload", 0x16, Kind::Local, "", "J"),
+    ("fload", 0x17, Kind::Local, "", "F"), ("dload", 0x18, Kind::Local, "", "D"),
+    ("aload", 0x19, Kind::Local, "", "A"),
+    ("iaload", 0x2E, Kind::Plain, "AI", "I"), ("laload", 0x2F, Kind::Plain, "AI", "J"),
+    ("faload", 0x30, Kind::Plain, "AI", "F"), ("daload", 0x31, Kind::Plain, "AI", "D"),
+    ("aaload", 0x32, Kind::Plain, "AI", "A"), ("baload", 0x33, Kind::Plain, "AI", "I"),
+    ("caload", 0x34, Kind::Plain, "AI", "I"), ("saload", 0x35, Kind::Plain, "AI", "I"),
+    ("istore", 0x36, Kind::Local, "I", ""), ("lstore", 0x37, Kind::Local, "J", ""),
+    ("fstore", 0x38, Kind::Local, "F", ""), ("dstore", 0x39, Kind::Local, "D", ""),
+    ("astore", 0x3A, Kind::Local, "A", ""),
+    ("iastore", 0x4F, Kind::Plain, "AII", ""), ("lastore", 0x50, Kind::Plain, "AIJ", ""),
+    ("fastore", 0x51, Kind::Plain, "AIF", ""), ("dastore", 0x52, Kind::Plain, "AID", ""),
+    ("aastore", 0x53, Kind::Plain, "AIA", ""), ("bastore", 0x54, Kind::Plain, "AII", ""),
+    ("castore", 0x55, Kind::Plain, "AII", ""), ("sastore", 0x56, Kind::Plain, "AII", ""),
+    ("pop", 0x57, Kind::Plain, "", ""), ("pop2", 0x58, Kind::Plain, "", ""),
+    ("dup", 0x59, Kind::Plain, "", ""), ("dup_x1", 0x5A, Kind::Plain, "", ""),
+    ("dup_x2", 0x5B, Kind::Plain, "", ""), ("dup2", 0x5C, Kind::Plain, "", ""),
+    ("dup2_x1", 0x5D, Kind::Plain, "", ""), ("dup2_x2", 0x5E, Kind::Plain, "", ""),
+    ("swap", 0x5F, Kind::Plain, "", ""),
+    ("iadd", 0x60, Kind::Plain, "II", "I"), ("ladd", 0x61, Kind::Plain, "JJ", "J"),
+    ("fadd", 0x62, Kind::Plain, "FF", "F"), ("dadd", 0x63, Kind::Plain, "DD", "D"),
+    ("isub", 0x64, Kind::Plain, "II", "I"), ("lsub", 0x65, Kind::Plain, "JJ", "J"),
+    ("fsub", 0x66, Kind::Plain, "FF", "F"), ("dsub", 0x67, Kind::Plain, "DD", "D"),
+    ("imul", 0x68, Kind::Plain, "II", "I"), ("lmul", 0x69, Kind::Plain, "JJ", "J"),
+    ("fmul", 0x6A, Kind::Plain, "FF", "F"), ("dmul", 0x6B, Kind::Plain, "DD", "D"),
+    ("idiv", 0x6C, Kind::Plain, "II", "I"), ("ldiv", 0x6D, Kind::Plain, "JJ", "J"),
+    ("fdiv", 0x6E, Kind::Plain, "FF", "F"), ("ddiv", 0x6F, Kind::Plain, "DD", "D"),
+    ("irem", 0x70, Kind::Plain, "II", "I"), ("lrem", 0x71, Kind::Plain, "JJ", "J"),
+    ("frem", 0x72, Kind::Plain, "FF", "F"), ("drem", 0x73, Kind::Plain, "DD", "D"),
+    ("ineg", 0x74, Kind::Plain, "I", "I"), ("lneg", 0x75, Kind::Plain, "J", "J"),
+    ("fneg", 0x76, Kind::Plain, "F", "F"), ("dneg", 0x77, Kind::Plain, "D", "D"),
+    ("ishl", 0x78, Kind::Plain, "II", "I"), ("lshl", 0x79, Kind::Plain, "JI", "J"),
+    ("ishr", 0x7A, Kind::Plain, "II", "I"), ("lshr", 0x7B, Kind::Plain, "JI", "J"),
+    ("iushr", 0x7C, Kind::Plain, "II", "I"), ("lushr", 0x7D, Kind::Plain, "JI", "J"),
+    ("iand", 0x7E, Kind::Plain, "II", "I"), ("land", 0x7F, Kind::Plain, "JJ", "J"),
+    ("ior", 0x80, Kind::Plain, "II", "I"), ("lor", 0x81, Kind::Plain, "JJ", "J"),
+    ("ixor", 0x82, Kind::Plain, "II", "I"), ("lxor", 0x83, Kind::Plain, "JJ", "J"),
+    ("iinc", 0x84, Kind::Iinc, "", ""),
+    ("i2l", 0x85, Kind::Plain, "I", "J"), ("i2f", 0x86, Kind::Plain, "I", "F"),
+    ("i2d", 0x87, Kind::Plain, "I", "D"), ("l2i", 0x88, Kind::Plain, "J", "I"),
+    ("l2f", 0x89, Kind::Plain, "J", "F"), ("l2d", 0x8A, Kind::Plain, "J", "D"),
+    ("f2i", 0x8B, Kind::Plain, "F", "I"), ("f2l", 0x8C, Kind::Plain, "F", "J"),
+    ("f2d", 0x8D, Kind::Plain, "F", "D"), ("d2i", 0x8E, Kind::Plain, "D", "I"),
+    ("d2l", 0x8F, Kind::Plain, "D", "J"), ("d2f", 0x90, Kind::Plain, "D", "F"),
+    ("i2b", 0x91, Kind::Plain, "I", "I"), ("i2c", 0x92, Kind::Plain, "I", "I"),
+    ("i2s", 0x93, Kind::Plain, "I", "I"),
+    ("lcmp", 0x94, Kind::Plain, "JJ", "I"), ("fcmpl", 0x95, Kind::Plain, "FF", "I"),
+    ("fcmpg", 0x96, Kind::Plain, "FF", "I"), ("dcmpl", 0x97, Kind::Plain, "DD", "I"),
+    ("dcmpg", 0x98, Kind::Plain, "DD", "I"),
+    ("ifeq", 0x99, Kind::Jump, "I", ""), ("ifne", 0x9A, Kind::Jump, "I", ""),
+    ("iflt", 0x9B, Kind::Jump, "I", ""), ("ifge", 0x9C, Kind::Jump, "I", ""),
+    ("ifgt", 0x9D, Kind::Jump, "I", ""), ("ifle", 0x9E, Kind::Jump, "I", ""),
+    ("if_icmpeq", 0x9F, Kind::Jump, "II", ""), ("if_icmpne", 0xA0, Kind::Jump, "II", ""),
+    ("if_icmplt", 0xA1, Kind::Jump, "II", ""), ("if_icmpge", 0xA2, Kind::Jump, "II", ""),
+    ("if_icmpgt", 0xA3, Kind::Jump, "II", ""), ("if_icmple", 0xA4, Kind::Jump, "II", ""),
+    ("if_acmpeq", 0xA5, Kind::Jump, "AA", ""), ("if_acmpne", 0xA6, Kind::Jump, "AA", ""),
+    ("goto", 0xA7, Kind::Jump, "", ""),
+    ("ireturn", 0xAC, Kind::Plain, "I", ""), ("lreturn", 0xAD, Kind::Plain, "J", ""),
+    ("freturn", 0xAE, Kind::Plain, "F", ""), ("dreturn", 0xAF, Kind::Plain, "D", ""),
+    ("areturn", 0xB0, Kind::Plain, "A", ""), ("return", 0xB1, Kind::Plain, "", ""),
+    ("getstatic", 0xB2, Kind::Field, "", ""), ("putstatic", 0xB3, Kind::Field, "", ""),
+    ("getfield", 0xB4, Kind::Field, "", ""), ("putfield", 0xB5, Kind::Field, "", ""),
+    ("invokevirtual", 0xB6, Kind::Method, "A", ""), ("invokespecial", 0xB7, Kind::Method, "A", ""),
+    ("invokestatic", 0xB8, Kind::Method, "", ""), ("invokeinterface", 0xB9, Kind::Method, "A", ""),
+    ("new", 0xBB, Kind::Class, "", "A"), ("newarray", 0xBC, Kind::NewArray, "I", "A"),
+    ("anewarray", 0xBD, Kind::Class, "I", "A"), ("arraylength", 0xBE, Kind::Plain, "A", "I"),
+    ("athrow", 0xBF, Kind::Plain, "A", ""),
+    ("checkcast", 0xC0, Kind::Class, "A", "A"), ("instanceof", 0xC1, Kind::Class, "A", "I"),
+    ("monitorenter", 0xC2, Kind::Plain, "A", ""), ("monitorexit", 0xC3, Kind::Plain, "A", ""),
+    ("ifnull", 0xC6, Kind::Jump, "A", ""), ("ifnonnull", 0xC7, Kind::Jump, "A", ""),
+];
+
+fn lookup(name: &str) -> Option<&'static (&'static str, u8, Kind, &'static str, &'static str)> {
+    TABLE.iter().find(|(n, ..)| *n == name)
+}
+
+fn kind_of(opcode: u8) -> Kind {
+    TABLE
+        .iter()
+        .find(|(_, op, ..)| *op == opcode)
+        .map_or(Kind::Plain, |e| e.2)
+}
+
+// Quita un comentario `--` que no esté dentro de un string
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '-' if !in_string && line[i..].starts_with("--") => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn is_identifier(s: &str) -> bool {
+    s.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+pub fn parse(body: &str) -> Result<Vec<AsmItem>, String> {
+    let mut items = Vec::new();
+    for (i, raw) in body.lines().enumerate() {
+        let line = i + 1;
+        let text = strip_comment(raw).trim();
+        if text.is_empty() {
+            continue;
+        }
+        if let Some(label) = text.strip_suffix(':') {
+            if !is_identifier(label) {
+                return Err(format!(
+                    "asm, línea {}: etiqueta inválida '{}'",
+                    line, label
+                ));
+            }
+            items.push(AsmItem::Label(label.to_string()));
+            continue;
+        }
+        let (name, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let rest = rest.trim();
+        let &(name, opcode, kind, ..) = lookup(name).ok_or(format!(
+            "asm, línea {}: instrucción desconocida '{}'",
+            line, name
+        ))?;
+        let err = |msg: &str| format!("asm, línea {}: {} {}", line, name, msg);
+        let int = |s: &str| {
+            s.parse::<i32>()
+                .map_err(|_| err(&format!("espera un entero, se encontró '{}'", s)))
+        };
+        if (kind == Kind::Plain) != rest.is_empty() {
+            return Err(err(if kind == Kind::Plain {
+                "no lleva operandos"
+            } else {
+                "necesita un operando"
+            }));
+        }
+        let operand = match kind {
+            Kind::Plain => Operand::None,
+            Kind::Byte | Kind::Short => {
+                let value = int(rest)?;
+                let range = if kind == Kind::Byte {
+                    -128..=127
+                } else {
+                    -32768..=32767
+                };
+                if !range.contains(&value) {
+                    return Err(err(&format!("fuera de rango: {}", value)));
+                }
+                Operand::Int(value)
+            }
+            Kind::Ldc => match rest.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+                Some(text) => Operand::Str(text.to_string()),
+                None => Operand::Int(int(rest)?),
+            },
+            Kind::Local | Kind::Jump if !is_identifier(rest) => {
+                return Err(err(&format!("espera un nombre, se encontró '{}'", rest)));
+            }
+            Kind::Local => Operand::Local(rest.to_string()),
+            Kind::Jump => Operand::Label(rest.to_string()),
+            Kind::Iinc => {
+                let (local, amount) = rest
+                    .split_once(char::is_whitespace)
+                    .ok_or(err("espera una local y un incremento"))?;
+                let amount = int(amount.trim())?;
+                if !is_identifier(local) || !(-128..=127).contains(&amount) {
+                    return Err(err("espera una local y un incremento entre -128 y 127"));
+                }
+                Operand::Iinc(local.to_string(), amount)
+            }
+            Kind::Class => Operand::Class(rest.to_string()),
+            Kind::NewArray => {
+                let atype = [
+                    "boolean", "char", "float", "double", "byte", "short", "int", "long",
+                ]
+                .iter()
+                .position(|t| *t == rest)
+                .ok_or(err(&format!("tipo primitivo desconocido '{}'", rest)))?;
+                Operand::ArrayType(atype as u8 + 4)
+            }
+            Kind::Field => {
+                // java/lang/System.out Ljava/io/PrintStream;
+                let (member, desc) = rest
+                    .split_once(char::is_whitespace)
+                    .ok_or(err("espera 'Clase.campo descriptor'"))?;
+                let (owner, field) = member
+                    .rsplit_once('.')
+                    .ok_or(err("espera 'Clase.campo descriptor'"))?;
+                Operand::Member(
+                    owner.to_string(),
+                    field.to_string(),
+                    desc.trim().to_string(),
+                )
+            }
+            Kind::Method => {
+                // java/io/PrintStream.println(Ljava/lang/String;)V
+                let open = rest
+                    .find('(')
+                    .ok_or(err("espera 'Clase.método(descriptor)retorno'"))?;
+                let (owner, method) = rest[..open]
+                    .rsplit_once('.')
+                    .ok_or(err("espera 'Clase.método(descriptor)retorno'"))?;
+                Operand::Member(
+                    owner.to_string(),
+                    method.to_string(),
+                    rest[open..].to_string(),
+                )
+            }
+        };
+        items.push(AsmItem::Instr(Instr {
+            line,
+            name,
+            opcode,
+            operand,
+        }));
+    }
+    Ok(items)
+}
+
+// Separa un descriptor de método en (parámetros, retorno)
+fn method_desc(desc: &str) -> Option<(Vec<VType>, Option<VType>)> {
+    let close = desc.find(')')?;
+    if !desc.starts_with('(') || close + 1 == desc.len() {
+        return None;
+    }
+    let mut params = Vec::new();
+    let mut chars = desc[1..close].chars().peekable();
+    while let Some(c) = chars.next() {
+        let mut c = c;
+        let is_array = c == '[';
+        while c == '[' {
+            c = chars.next()?;
+        }
+        if c == 'L' {
+            chars.by_ref().find(|&c| c == ';')?;
+        }
+        params.push(if is_array {
+            VType::Ref
+        } else {
+            VType::from_char(c)
+        });
+    }
+    Some((params, VType::from_desc(&desc[close + 1..])))
+}
+
+// Simula la pila por todos los caminos: no puede vaciarse de más, debe coincidir
+// donde se juntan dos caminos y quedar vacía al final del bloque, como la deja
+// el código que lo rodea. `ret` es el retorno de la función (None si es Void).
+pub fn validate(
+    items: &[AsmItem],
+    local: impl Fn(&str) -> Result<VType, String>,
+    ret: Option<VType>,
+) -> Result<(), String> {
+    let mut labels = HashMap::new();
+    let mut instrs = Vec::new();
+    for item in items {
+        match item {
+            AsmItem::Label(name) => {
+                if labels.insert(name.clone(), instrs.len()).is_some() {
+                    return Err(format!("asm: la etiqueta '{}' está repetida", name));
+                }
+            }
+            AsmItem::Instr(instr) => instrs.push(instr),
+        }
+    }
+
+    let mut states: Vec<Option<Vec<VType>>> = vec![None; instrs.len() + 1];
+    let mut pending = vec![(0, Vec::new())];
+    while let Some((pc, stack)) = pending.pop() {
+        match &states[pc] {
+            Some(previous) if *previous == stack => continue,
+            Some(previous) => {
+                let at = instrs.get(pc).map_or("el final del bloque".into(), |i| {
+                    format!("la línea {}", i.line)
+                });
+                return Err(format!(
+                    "asm: la pila llega a {} con contenidos distintos ({:?} y {:?})",
+                    at, previous, stack
+                ));
+            }
+            None => states[pc] = Some(stack.clone()),
+        }
+        let Some(instr) = instrs.get(pc) else {
+            if !stack.is_empty() {
+                return Err(format!(
+                    "asm: el bloque termina con {} valor(es) en la pila; debe dejarla vacía",
+                    stack.len()
+                ));
+            }
+            continue;
+        };
+        let mut stack = stack;
+        step(instr, &mut stack, &local, ret)?;
+        let slots: usize = stack.iter().map(|v| v.slots()).sum();
+        if slots > MAX_STACK {
+            return Err(format!(
+                "asm, línea {}: la pila supera el máximo de {} slots",
+                instr.line, MAX_STACK
+            ));
+        }
+        if let Operand::Label(label) = &instr.operand {
+            let target = *labels.get(label).ok_or(format!(
+                "asm, línea {}: etiqueta '{}' no definida",
+                instr.line, label
+            ))?;
+            pending.push((target, stack.clone()));
+        }
+        let falls_through = !matches!(instr.opcode, 0xA7 | 0xAC..=0xB1 | 0xBF);
+        if falls_through {
+            pending.push((pc + 1, stack));
+        }
+    }
+    Ok(())
+}
+
+// Si algún camino sale del bloque por abajo (sin return, athrow ni goto final)
+pub fn reaches_end(items: &[AsmItem]) -> bool {
+    let mut labels = HashMap::new();
+    let mut instrs = Vec::new();
+    for item in items {
+        match item {
+            AsmItem::Label(name) => {
+                labels.insert(name.as_str(), instrs.len());
+            }
+            AsmItem::Instr(instr) => instrs.push(instr),
+        }
+    }
+    let mut visited = vec![false; instrs.len() + 1];
+    let mut pending = vec![0];
+    while let Some(pc) = pending.pop() {
+        if std::mem::replace(&mut visited[pc], true) {
+            continue;
+        }
+        let Some(instr) = instrs.get(pc) else {
+            continue;
+        };
+        if let Operand::Label(label) = &instr.operand
+            && let Some(&target) = labels.get(label.as_str())
+        {
+            pending.push(target);
+        }
+        if !matches!(instr.opcode, 0xA7 | 0xAC..=0xB1 | 0xBF) {
+            pending.push(pc + 1);
+        }
+    }
+    visited[instrs.len()]
+}
+
+fn step(
+    instr: &Instr,
+    stack: &mut Vec<VType>,
+    local: &impl Fn(&str) -> Result<VType, String>,
+    ret: Option<VType>,
+) -> Result<(), String> {
+    let line = instr.line;
+    let name = instr.name;
+    let pop = |stack: &mut Vec<VType>, expected: Option<VType>| -> Result<VType, String> {
+        let v = stack
+            .pop()
+            .ok_or(format!("asm, línea {}: {} con la pila vacía", line, name))?;
+        match expected {
+            Some(e) if e != v => Err(format!(
+                "asm, línea {}: {} espera {:?} en la pila, encontró {:?}",
+                line, name, e, v
+            )),
+            _ => Ok(v),
+        }
+    };
+    let pop_all = |stack: &mut Vec<VType>, types: &[VType]| -> Result<(), String> {
+        for t in types.iter().rev() {
+            pop(stack, Some(*t))?;
+        }
+        Ok(())
+    };
+    let (_, _, kind, pops, pushes) = lookup(name).expect("instrucción de la tabla");
+    let pops: Vec<VType> = pops.chars().map(VType::from_char).collect();
+    let mut pushes: Vec<VType> = pushes.chars().map(VType::from_char).collect();
+    let cat2 = |v: VType| v.slots() == 2;
+
+    match (instr.opcode, &instr.operand) {
+        // Instrucciones de pila: dependen de la categoría de los valores
+        (0x57, _) => {
+            if cat2(pop(stack, None)?) {
+                return Err(format!(
+                    "asm, línea {}: pop sobre un long/double (usa pop2)",
+                    line
+                ));
+            }
+        }
+        (0x58, _) => {
+            if !cat2(pop(stack, None)?) && cat2(pop(stack, None)?) {
+                return Err(format!("asm, línea {}: pop2 partiría un long/double", line));
+            }
+        }
+        (0x59..=0x5B, _) => {
+            let v = pop(stack, None)?;
+            if cat2(v) {
+                return Err(format!(
+                    "asm, línea {}: {} sobre un long/double (usa dup2)",
+                    line, name
+                ));
+            }
+            let mut under = Vec::new();
+            let depth = match instr.opcode {
+                0x5A => 1,
+                0x5B => 2,
+                _ => 0,
+            };
+            let mut slots = 0;
+            while slots < depth {
+                let u = pop(stack, None)?;
+                slots += u.slots();
+                under.push(u);
+            }
+            if slots != depth {
+                return Err(format!(
+                    "asm, línea {}: {} partiría un long/double",
+                    line, name
+                ));
+            }
+            stack.push(v);
+            stack.extend(under.into_iter().rev());
+            stack.push(v);
+        }
+        (0x5C..=0x5E, _) => {
+            let mut top = vec![pop(stack, None)?];
+            if !cat2(top[0]) {
+                let second = pop(stack, None)?;
+                if cat2(second) {
+                    return Err(format!(
+                        "asm, línea {}: {} partiría un long/double",
+                        line, name
+                    ));
+                }
+                top.insert(0, second);
+            }
+            let depth = instr.opcode as usize - 0x5C;
+            let (mut under, mut slots) = (Vec::new(), 0);
+            while slots < depth {
+                let u = pop(stack, None)?;
+                slots += u.slots();
+                under.push(u);
+            }
+            if slots != depth {
+                return Err(format!(
+                    "asm, línea {}: {} partiría un long/double",
+                    line, name
+                ));
+            }
+            stack.extend(top.iter().copied());
+            stack.extend(under.into_iter().rev());
+            stack.extend(top);
+        }
+        (0x5F, _) => {
+            let a = pop(stack, None)?;
+            let b = pop(stack, None)?;
+            if cat2(a) || cat2(b) {
+                return Err(format!("asm, línea {}: swap sobre un long/double", line));
+            }
+            stack.extend([a, b]);
+        }
+        (0xAC..=0xB1, _) => {
+            let expected = if instr.opcode == 0xB1 {
+                None
+            } else {
+                pops.first().copied()
+            };
+            if expected != ret {
+                return Err(format!(
+                    "asm, línea {}: {} no corresponde al retorno de la función ({})",
+                    line,
+                    name,
+                    ret.map_or("Void".into(), |r| format!("{:?}", r))
+                ));
+            }
+            pop_all(stack, &pops)?;
+        }
+        (_, Operand::Int(_)) if *kind == Kind::Ldc => stack.push(VType::Int),
+        (_, Operand::Str(_)) => stack.push(VType::Ref),
+        (_, Operand::Local(n) | Operand::Iinc(n, _)) => {
+            let local_t = local(n).map_err(|e| format!("asm, línea {}: {}", line, e))?;
+            let expected = match *kind {
+                Kind::Iinc => VType::Int,
+                _ => pops
+                    .first()
+                    .or(pushes.first())
+                    .copied()
+                    .unwrap_or(VType::Int),
+            };
+            if local_t != expected {
+                return Err(format!(
+                    "asm, línea {}: {} necesita una local {:?}, '{}' es {:?}",
+                    line, name, expected, n, local_t
+                ));
+            }
+            pop_all(stack, &pops)?;
+            stack.append(&mut pushes);
+        }
+        (_, Operand::Member(_, _, desc)) if *kind == Kind::Field => {
+            let field_t = VType::from_desc(desc).ok_or(format!(
+                "asm, línea {}: descriptor de campo inválido '{}'",
+                line, desc
+            ))?;
+            match instr.opcode {
+                0xB2 => stack.push(field_t), // getstatic
+                0xB3 => {
+                    pop(stack, Some(field_t))?;
+                }
+                0xB4 => {
+                    pop(stack, Some(VType::Ref))?;
+                    stack.push(field_t);
+                }
+                _ => {
+                    // putfield: el objeto va debajo del valor
+                    pop(stack, Some(field_t))?;
+                    pop(stack, Some(VType::Ref))?;
+                }
+            }
+        }
+        (_, Operand::Member(_, _, desc)) => {
+            let (params, method_ret) = method_desc(desc).ok_or(format!(
+                "asm, línea {}: descriptor de método inválido '{}'",
+                line, desc
+            ))?;
+            pop_all(stack, &params)?;
+            pop_all(stack, &pops)?; // El receptor, salvo en invokestatic
+            stack.extend(method_ret);
+        }
+        _ => {
+            pop_all(stack, &pops)?;
+            stack.append(&mut pushes);
+        }
+    }
+    Ok(())
+}
+
+// Bytecode del bloque; los saltos se resuelven con el tamaño de cada instrucción
+pub fn assemble(items: &[AsmItem], cp: &mut ConstantPool, slot: impl Fn(&str) -> u8) -> Vec<u8> {
+    let mut labels = HashMap::new();
+    let mut encoded: Vec<(Vec<u8>, Option<&str>)> = Vec::new(); // (bytes, etiqueta a parchear)
+    let mut offset = 0;
+    for item in items {
+        let instr = match item {
+            AsmItem::Label(name) => {
+                labels.insert(name.as_str(), offset);
+                continue;
+            }
+            AsmItem::Instr(instr) => instr,
+        };
+        let mut bytes = vec![instr.opcode];
+        let mut jump = None;
+        match &instr.operand {
+            Operand::None => {}
+            Operand::Int(value) if instr.opcode == 0x10 => bytes.push(*value as u8),
+            Operand::Int(value) if instr.opcode == 0x11 => {
+                bytes.extend_from_slice(&(*value as i16).to_be_bytes())
+            }
+            Operand::Int(value) => {
+                let idx = cp.add_integer(*value);
+                ldc(&mut bytes, idx);
+            }
+            Operand::Str(text) => {
+                let utf8 = cp.add_utf8(text);
+                let idx = cp.add_string(utf8);
+                ldc(&mut bytes, idx);
+            }
+            Operand::Local(name) => bytes.push(slot(name)),
+            Operand::Iinc(name, amount) => bytes.extend_from_slice(&[slot(name), *amount as u8]),
+            Operand::Label(label) => {
+                bytes.extend_from_slice(&[0, 0]);
+                jump = Some(label.as_str());
+            }
+            Operand::Class(name) => {
+                let utf8 = cp.add_utf8(name);
+                bytes.extend_from_slice(&cp.add_class(utf8).to_be_bytes());
+            }
+            Operand::ArrayType(atype) => bytes.push(*atype),
+            Operand::Member(owner, member, desc) => {
+                let owner_utf8 = cp.add_utf8(owner);
+                let class_idx = cp.add_class(owner_utf8);
+                let name_idx = cp.add_utf8(member);
+                let desc_idx = cp.add_utf8(desc);
+                let nt = cp.add_name_and_type(name_idx, desc_idx);
+                let idx = match kind_of(instr.opcode) {
+                    Kind::Field => cp.add_field_ref(class_idx, nt),
+                    _ if instr.opcode == 0xB9 => cp.add_interface_method_ref(class_idx, nt),
+                    _ => cp.add_method_ref(class_idx, nt),
+                };
+                bytes.extend_from_slice(&idx.to_be_bytes());
+                if instr.opcode == 0xB9 {
+                    // invokeinterface: slots de los argumentos más el receptor, y un 0
+                    let slots: usize =
+                        method_desc(desc).map_or(0, |(p, _)| p.iter().map(|v| v.slots()).sum());
+                    bytes.extend_from_slice(&[1 + slots as u8, 0]);
+                }
+            }
+        }
+        offset += bytes.len();
+        encoded.push((bytes, jump));
+    }
+
+    let mut out = Vec::new();
+    for (mut bytes, jump) in encoded {
+        if let Some(label) = jump {
+            let delta = (labels[label] as i32 - out.len() as i32) as i16;
+            bytes[1..3].copy_from_slice(&delta.to_be_bytes());
+        }
+        out.extend(bytes);
+    }
+    out
+}
+
+fn ldc(bytes: &mut Vec<u8>, idx: u16) {
+    if idx <= u8::MAX as u16 {
+        bytes.push(idx as u8);
+    } else {
+        bytes[0] = 0x13; // ldc_w
+        bytes.extend_from_slice(&idx.to_be_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_locals(name: &str) -> Result<VType, String> {
+        Err(format!("'{}' no existe", name))
+    }
+
+    fn int_local(_: &str) -> Result<VType, String> {
+        Ok(VType::Int)
+    }
+
+    fn assemble_text(body: &str) -> Vec<u8> {
+        let items = parse(body).unwrap();
+        assemble(&items, &mut ConstantPool::new(), |_| 1)
+    }
+
+    #[test]
+    fn forward_jump_skips_to_its_label() {
+        let body = "iconst_1\nifeq fin\niconst_2\npop\nfin:\n";
+        let items = parse(body).unwrap();
+        assert_eq!(validate(&items, no_locals, None), Ok(()));
+        // ifeq en el offset 1 salta 5 bytes hasta el final (offset 6)
+        assert_eq!(assemble_text(body), [0x04, 0x99, 0x00, 0x05, 0x05, 0x57]);
+    }
+
+    #[test]
+    fn backward_jump_has_a_negative_offset() {
+        let body = "inicio:\niinc x -1\niload x\nifgt inicio\n";
+        let items = parse(body).unwrap();
+        assert_eq!(validate(&items, int_local, None), Ok(()));
+        // iinc(3) + iload(2): ifgt en el offset 5 vuelve al 0
+        assert_eq!(
+            assemble_text(body),
+            [0x84, 0x01, 0xFF, 0x15, 0x01, 0x9D, 0xFF, 0xFB]
+        );
+    }
+
+    #[test]
+    fn unknown_label_is_rejected() {
+        let items = parse("goto nada\n").unwrap();
+        let err = validate(&items, no_locals, None).unwrap_err();
+        assert!(err.contains("etiqueta 'nada' no definida"), "{}", err);
+    }
+
+    #[test]
+    fn stack_underflow_is_rejected() {
+        let items = parse("iconst_1\niadd\n").unwrap();
+        let err = validate(&items, no_locals, None).unwrap_err();
+        assert!(err.contains("con la pila vacía"), "{}", err);
+    }
+
+    #[test]
+    fn stack_overflow_is_rejected() {
+        let pushes = "lconst_0\n".repeat(MAX_STACK / 2 + 1);
+        let items = parse(&pushes).unwrap();
+        let err = validate(&items, no_locals, None).unwrap_err();
+        assert!(err.contains("la pila supera el máximo"), "{}", err);
+    }
+}
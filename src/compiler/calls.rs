@@ -0,0 +1,143 @@
+// src/compiler/calls.rs
+use crate::compiler::constants::ConstValue;
+use crate::compiler::java_symbols::{JavaMethod, JavaSymbols};
+use crate::compiler::types::KType;
+use crate::parser::ast::{Arg, Expr};
+use std::collections::HashMap;
+
+// Firma de una función Kujav; los valores por defecto son constantes de compilación
+#[derive(Debug, Clone)]
+pub struct FunctionSig {
+    pub params: Vec<(String, KType, Option<ConstValue>)>,
+    pub ret: KType,
+    pub variadic: bool, // El último parámetro es `...nombre: T` (un arreglo T[])
+}
+
+impl FunctionSig {
+    // Parámetros obligatorios: los que tienen valor por defecto van siempre al final
+    pub fn required(&self) -> usize {
+        self.params
+            .iter()
+            .take_while(|(_, _, d)| d.is_none())
+            .count()
+    }
+}
+
+// Destino de `valor.método(args)`
+pub enum MethodCall {
+    Java(JavaMethod),
+    Extension(String), // Clave de la extensión en la tabla de funciones: "Player.heal"
+}
+
+pub const USER_MAIN: &str = "kujav$main"; // El main(String[]) de la clase lo genera el compilador
+
+// Nombre del método en el class file: una extensión `Player.heal` se compila como
+// `heal` estático con el receptor como primer parámetro
+pub fn jvm_name(name: &str) -> &str {
+    if name == "main" {
+        return USER_MAIN;
+    }
+    name.rsplit_once('.').map_or(name, |(_, method)| method)
+}
+
+// Primero los métodos reales del tipo del receptor; si no tiene ninguno con ese
+// nombre, la extensión más específica cuyo `self` acepta al receptor
+pub fn resolve_method(
+    java: &JavaSymbols,
+    functions: &HashMap<String, FunctionSig>,
+    receiver: &KType,
+    method: &str,
+    args: &[KType],
+) -> Result<MethodCall, String> {
+    if let Some(m) = java.resolve_instance(receiver, method, args)? {
+        return Ok(MethodCall::Java(m));
+    }
+    functions
+        .iter()
+        .filter(|(name, _)| name.contains('.') && jvm_name(name) == method)
+        .filter_map(|(name, sig)| Some((java.arg_cost(&sig.params[0].1, receiver)?, name)))
+        // El receptor se pasa sin convertir: mismo tipo o un subtipo
+        .filter(|(cost, _)| matches!(cost, 0 | 2))
+        .min_by_key(|(cost, name)| (*cost, name.as_str()))
+        .map(|(_, name)| MethodCall::Extension(name.clone()))
+        .ok_or(format!(
+            "{} no tiene un método '{}' ni existe una extensión que lo defina",
+            receiver, method
+        ))
+}
+
+// Ordena los argumentos por parámetro: primero los posicionales, luego los que
+// llevan nombre, y los que faltan se completan con su valor por defecto. En una
+// función variádica los posicionales sobrantes se agrupan en un ArrayLiteral;
+// pasar `args = arreglo` por nombre entrega el arreglo tal cual.
+pub fn resolve_args(name: &str, sig: &FunctionSig, args: &[Arg]) -> Result<Vec<Expr>, String> {
+    let mut slots: Vec<Option<Expr>> = vec![None; sig.params.len()];
+    let mut packed = Vec::new();
+    let mut named_seen = false;
+    for (i, (arg_name, value)) in args.iter().enumerate() {
+        if arg_name.is_none() && !named_seen && sig.variadic && i + 1 >= sig.params.len() {
+            packed.push(value.clone());
+            continue;
+        }
+        let pos = match arg_name {
+            None if named_seen => {
+                return Err(format!(
+                    "En la llamada a '{}' hay un argumento posicional después de uno con nombre",
+                    name
+                ));
+            }
+            None if i >= sig.params.len() => {
+                return Err(format!(
+                    "'{}' recibe como máximo {} argumentos, se pasaron {}",
+                    name,
+                    sig.params.len(),
+                    args.len()
+                ));
+            }
+            None => i,
+            Some(arg_name) => {
+                named_seen = true;
+                sig.params
+                    .iter()
+                    .position(|(p, _, _)| p == arg_name)
+                    .ok_or(format!("'{}' no tiene un parámetro '{}'", name, arg_name))?
+            }
+        };
+        if slots[pos].is_some() {
+            return Err(format!(
+                "El parámetro '{}' de '{}' recibe dos valores",
+                sig.params[pos].0, name
+            ));
+        }
+        slots[pos] = Some(value.clone());
+    }
+    if sig.variadic {
+        let last = slots
+            .last_mut()
+            .expect("una función variádica tiene parámetros");
+        match last {
+            Some(_) if !packed.is_empty() => {
+                return Err(format!(
+                    "El parámetro '{}' de '{}' recibe dos valores",
+                    sig.params[sig.params.len() - 1].0,
+                    name
+                ));
+            }
+            Some(_) => {}
+            None => *last = Some(Expr::ArrayLiteral(packed)),
+        }
+    }
+
+    slots
+        .into_iter()
+        .zip(&sig.params)
+        .map(|(slot, (p_name, _, default))| match (slot, default) {
+            (Some(value), _) => Ok(value),
+            (None, Some(d)) => Ok(d.to_expr()),
+            (None, None) => Err(format!(
+                "Falta el argumento '{}' en la llamada a '{}'",
+                p_name, name
+            )),
+        })
+        .collect()
+}
@@ -0,0 +1,98 @@
+// src/compiler/cfg.rs
+// Compilación condicional: resuelve los @cfg(...) entre el parser y el análisis semántico
+use crate::parser::ast::{CfgPredicate, Stmt};
+use std::collections::BTreeSet;
+
+pub struct CfgContext {
+    pub features: BTreeSet<String>,       // Features activas en este build
+    pub known_features: BTreeSet<String>, // Todas las declaradas en [features]
+    pub profile: String,
+    pub known_profiles: BTreeSet<String>,
+}
+
+impl CfgContext {
+    // Los nombres desconocidos son error aunque la rama no se tome
+    fn eval(&self, predicate: &CfgPredicate) -> Result<bool, String> {
+        match predicate {
+            CfgPredicate::Feature(name) => {
+                if !self.known_features.contains(name) || name == "default" {
+                    return Err(format!(
+                        "Feature desconocida '{}' en @cfg: declárala en [features] de kujav.toml",
+                        name
+                    ));
+                }
+                Ok(self.features.contains(name))
+            }
+            CfgPredicate::Profile(name) => {
+                if !self.known_profiles.contains(name) {
+                    let known: Vec<&str> = self.known_profiles.iter().map(String::as_str).collect();
+                    return Err(format!(
+                        "Perfil desconocido '{}' en @cfg (perfiles: {})",
+                        name,
+                        known.join(", ")
+                    ));
+                }
+                Ok(*name == self.profile)
+            }
+            CfgPredicate::Not(inner) => Ok(!self.eval(inner)?),
+            CfgPredicate::All(items) => {
+                let values = items
+                    .iter()
+                    .map(|p| self.eval(p))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(values.into_iter().all(|v| v))
+            }
+            CfgPredicate::Any(items) => {
+                let values = items
+                    .iter()
+                    .map(|p| self.eval(p))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(values.into_iter().any(|v| v))
+            }
+        }
+    }
+
+    // Quita las declaraciones y sentencias desactivadas, también dentro de bloques
+    pub fn apply(&self, stmts: Vec<Stmt>) -> Result<Vec<Stmt>, String> {
+        let mut out = Vec::new();
+        for stmt in stmts {
+            if let Some(stmt) = self.apply_stmt(stmt)? {
+                out.push(stmt);
+            }
+        }
+        Ok(out)
+    }
+
+    fn apply_stmt(&self, stmt: Stmt) -> Result<Option<Stmt>, String> {
+        Ok(Some(match stmt {
+            Stmt::Cfg(predicate, inner) => {
+                if !self.eval(&predicate)? {
+                    // Se recorre igualmente para validar los @cfg anidados
+                    self.apply_stmt(*inner)?;
+                    return Ok(None);
+                }
+                return self.apply_stmt(*inner);
+            }
+            Stmt::Function(name, params, body, ret, annotations, doc, visibility) => {
+                Stmt::Function(
+                    name,
+                    params,
+                    self.apply(body)?,
+                    ret,
+                    annotations,
+                    doc,
+                    visibility,
+                )
+            }
+            Stmt::If(cond, then_body, else_body) => {
+                let else_body = else_body.map(|b| self.apply(b)).transpose()?;
+                Stmt::If(cond, self.apply(then_body)?, else_body)
+            }
+            Stmt::While(cond, body) => Stmt::While(cond, self.apply(body)?),
+            Stmt::For(var, iterable, body, span) => {
+                Stmt::For(var, iterable, self.apply(body)?, span)
+            }
+            other => other,
+        }))
+    }
+}
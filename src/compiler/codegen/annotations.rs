@@ -0,0 +1,122 @@
+// src/compiler/codegen/annotations.rs
+use crate::compiler::annotations::{
+    ElementValue, ResolvedAnnotation, Retention, resolve_annotation,
+};
+use crate::compiler::codegen::Compiler;
+use crate::compiler::lints::ALLOW;
+use crate::parser::ast::Annotation;
+
+impl Compiler {
+    // Devuelve los atributos (nombre, contenido) Runtime[In]Visible[Parameter]Annotations
+    pub fn annotation_attributes(
+        &mut self,
+        annotations: &[Annotation],
+        target: &str,
+        param_annotations: &[&[Annotation]],
+    ) -> Vec<(u16, Vec<u8>)> {
+        let mut attributes = Vec::new();
+        let own = self.resolve_all(annotations, target);
+        let params: Vec<Vec<ResolvedAnnotation>> = param_annotations
+            .iter()
+            .map(|anns| self.resolve_all(anns, "PARAMETER"))
+            .collect();
+
+        for (retention, name, param_name) in [
+            (
+                Retention::Runtime,
+                "RuntimeVisibleAnnotations",
+                "RuntimeVisibleParameterAnnotations",
+            ),
+            (
+                Retention::Class,
+                "RuntimeInvisibleAnnotations",
+                "RuntimeInvisibleParameterAnnotations",
+            ),
+        ] {
+            let selected: Vec<&ResolvedAnnotation> =
+                own.iter().filter(|a| a.retention == retention).collect();
+            if !selected.is_empty() {
+                let mut bytes = (selected.len() as u16).to_be_bytes().to_vec();
+                for ann in selected {
+                    self.encode_annotation(ann, &mut bytes);
+                }
+                attributes.push((self.cp.add_utf8(name), bytes));
+            }
+
+            if params.iter().flatten().any(|a| a.retention == retention) {
+                let mut bytes = vec![params.len() as u8];
+                for p in &params {
+                    let selected: Vec<&ResolvedAnnotation> =
+                        p.iter().filter(|a| a.retention == retention).collect();
+                    bytes.extend_from_slice(&(selected.len() as u16).to_be_bytes());
+                    for ann in selected {
+                        self.encode_annotation(ann, &mut bytes);
+                    }
+                }
+                attributes.push((self.cp.add_utf8(param_name), bytes));
+            }
+        }
+        attributes
+    }
+
+    fn resolve_all(&self, annotations: &[Annotation], target: &str) -> Vec<ResolvedAnnotation> {
+        annotations
+            .iter()
+            .filter(|a| a.name != ALLOW)
+            .map(|a| {
+                resolve_annotation(a, &self.java, Some(target))
+                    .expect("anotación validada por SemanticAnalyzer")
+            })
+            .filter(|a| a.retention != Retention::Source)
+            .collect()
+    }
+
+    fn encode_annotation(&mut self, ann: &ResolvedAnnotation, out: &mut Vec<u8>) {
+        let type_idx = self.cp.add_utf8(&ann.descriptor);
+        out.extend_from_slice(&type_idx.to_be_bytes());
+        out.extend_from_slice(&(ann.elements.len() as u16).to_be_bytes());
+        for (name, value) in &ann.elements {
+            let name_idx = self.cp.add_utf8(name);
+            out.extend_from_slice(&name_idx.to_be_bytes());
+            self.encode_element_value(value, out);
+        }
+    }
+
+    fn encode_element_value(&mut self, value: &ElementValue, out: &mut Vec<u8>) {
+        match value {
+            ElementValue::Const(tag, v) => {
+                out.push(*tag);
+                let idx = self.cp.add_integer(*v);
+                out.extend_from_slice(&idx.to_be_bytes());
+            }
+            ElementValue::String(s) => {
+                out.push(b's');
+                let idx = self.cp.add_utf8(s);
+                out.extend_from_slice(&idx.to_be_bytes());
+            }
+            ElementValue::Enum { descriptor, name } => {
+                out.push(b'e');
+                let type_idx = self.cp.add_utf8(descriptor);
+                let name_idx = self.cp.add_utf8(name);
+                out.extend_from_slice(&type_idx.to_be_bytes());
+                out.extend_from_slice(&name_idx.to_be_bytes());
+            }
+            ElementValue::Class(descriptor) => {
+                out.push(b'c');
+                let idx = self.cp.add_utf8(descriptor);
+                out.extend_from_slice(&idx.to_be_bytes());
+            }
+            ElementValue::Annotation(inner) => {
+                out.push(b'@');
+                self.encode_annotation(inner, out);
+            }
+            ElementValue::Array(items) => {
+                out.push(b'[');
+                out.extend_from_slice(&(items.len() as u16).to_be_bytes());
+                for item in items {
+                    self.encode_element_value(item, out);
+                }
+            }
+        }
+    }
+}
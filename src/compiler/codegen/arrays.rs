@@ -0,0 +1,89 @@
+// src/compiler/codegen/arrays.rs
+//
+// Arreglos JVM: literales, Array<T>(n) y len(x).
+use crate::compiler::codegen::{Compiler, array_store_op, is_ref_sig};
+use crate::compiler::ir::TExpr;
+use crate::compiler::types::KType;
+
+impl Compiler {
+    // newarray/anewarray de un elemento; el tamaño ya está en la pila
+    pub fn emit_new_array(&mut self, elem_t: &KType) {
+        let elem_sig = self.java.type_sig(elem_t);
+        if is_ref_sig(&elem_sig) {
+            let class_idx = self.class_const(&self.java.class_ref(elem_t));
+            self.current_bytecode.push(0xBD); // anewarray
+            self.current_bytecode
+                .extend_from_slice(&class_idx.to_be_bytes());
+        } else {
+            self.current_bytecode
+                .extend_from_slice(&[0xBC, newarray_type(&elem_sig)]);
+        }
+    }
+
+    // `[a, b]` como arreglo, List o Set según su tipo; los elementos ya llegan
+    // convertidos (`[[]]` como String[][] da un String[] vacío)
+    pub fn compile_array_literal(&mut self, ktype: &KType, elems: Vec<TExpr>) {
+        match ktype {
+            KType::Array(elem_t) => self.compile_typed_array(elem_t, elems),
+            _ => self.compile_collection_literal(ktype, elems),
+        }
+    }
+
+    fn compile_typed_array(&mut self, elem_t: &KType, elems: Vec<TExpr>) {
+        let elem_sig = self.java.type_sig(elem_t);
+        self.emit_int(elems.len() as i32);
+        self.emit_new_array(elem_t);
+        for (i, e) in elems.into_iter().enumerate() {
+            self.current_bytecode.push(0x59); // dup
+            self.emit_int(i as i32);
+            self.compile_expression(e);
+            self.current_bytecode.push(array_store_op(&elem_sig));
+        }
+    }
+
+    // Array<T>(n) con un tamaño; con varios, multianewarray reserva todas las dimensiones
+    pub fn compile_new_array(&mut self, array_t: &KType, dims: Vec<TExpr>) {
+        let count = dims.len() as u8;
+        for d in dims {
+            self.compile_expression(d);
+        }
+        if count == 1 {
+            let KType::Array(elem_t) = array_t else {
+                unreachable!("Array<T>(n) es un arreglo");
+            };
+            self.emit_new_array(elem_t);
+        } else {
+            let class_idx = self.class_const(&self.java.class_ref(array_t));
+            self.current_bytecode.push(0xC5); // multianewarray
+            self.current_bytecode
+                .extend_from_slice(&class_idx.to_be_bytes());
+            self.current_bytecode.push(count);
+        }
+    }
+
+    // arraylength para arreglos; String.length() y size() para las colecciones
+    pub fn compile_length(&mut self, inner: TExpr) {
+        let t = inner.ktype.clone();
+        self.compile_expression(inner);
+        match t {
+            KType::Array(_) => self.current_bytecode.push(0xBE), // arraylength
+            KType::String => self.emit_invoke(0xB6, "java/lang/String", "length", "()I"),
+            KType::Map(_, _) => self.emit_invoke_interface("java/util/Map", "size", "()I"),
+            _ => self.emit_invoke_interface("java/util/Collection", "size", "()I"),
+        }
+    }
+}
+
+// Operando `atype` de newarray para un primitivo
+fn newarray_type(sig: &str) -> u8 {
+    match sig {
+        "Z" => 4,
+        "C" => 5,
+        "F" => 6,
+        "D" => 7,
+        "B" => 8,
+        "S" => 9,
+        "J" => 11,
+        _ => 10,
+    }
+}
@@ -0,0 +1,84 @@
+// src/compiler/codegen/assertions.rs
+//
+// `assert cond, mensaje` como lo compila javac: el campo sintético
+// `$assertionsDisabled` se calcula en <clinit> con desiredAssertionStatus(),
+// de modo que `java -ea` activa las comprobaciones.
+use crate::compiler::codegen::{Compiler, FieldInfo, is_ref_sig};
+use crate::compiler::ir::TExpr;
+
+const ASSERTIONS_FIELD: &str = "$assertionsDisabled";
+
+impl Compiler {
+    pub fn compile_assert(&mut self, cond: TExpr, message: Option<TExpr>) {
+        self.uses_assertions = true;
+        self.emit_field_access(0xB2, ASSERTIONS_FIELD, "Z");
+        let skip_disabled = self.emit_jump(0x9A); // ifne: desactivadas
+        self.compile_expression(cond);
+        let skip_ok = self.emit_jump(0x9A); // ifne: se cumple
+
+        let error_class = self.class_const("java/lang/AssertionError");
+        self.current_bytecode.push(0xBB); // new
+        self.current_bytecode
+            .extend_from_slice(&error_class.to_be_bytes());
+        self.current_bytecode.push(0x59); // dup
+        let desc = match message {
+            Some(msg) => {
+                let sig = self.java.type_sig(&msg.ktype);
+                self.compile_expression(msg);
+                // Los constructores de AssertionError para cada primitivo, y Object para el resto
+                match sig.as_str() {
+                    "B" | "S" => "(I)V".to_string(),
+                    s if is_ref_sig(s) => "(Ljava/lang/Object;)V".to_string(),
+                    s => format!("({})V", s),
+                }
+            }
+            None => "()V".to_string(),
+        };
+        self.emit_invoke(0xB7, "java/lang/AssertionError", "<init>", &desc);
+        self.current_bytecode.push(0xBF); // athrow
+
+        self.patch_jump(skip_disabled);
+        self.patch_jump(skip_ok);
+    }
+
+    // static final synthetic boolean $assertionsDisabled, calculado al principio de <clinit>
+    // para que también valga en los inicializadores de las globales
+    pub fn compile_assertions_field(&mut self) {
+        self.fields.push(FieldInfo {
+            access_flags: 0x1018, // static final synthetic
+            name_idx: self.cp.add_utf8(ASSERTIONS_FIELD),
+            sig_idx: self.cp.add_utf8("Z"),
+            attributes: Vec::new(),
+        });
+        let main = std::mem::take(&mut self.current_bytecode);
+        let class_name = self.class_name.clone();
+        let class_idx = self.class_const(&class_name);
+        self.emit_ldc(class_idx);
+        self.emit_invoke(0xB6, "java/lang/Class", "desiredAssertionStatus", "()Z");
+        let enabled = self.emit_jump(0x9A); // ifne
+        self.current_bytecode.push(0x04); // iconst_1: desactivadas
+        let done = self.emit_jump(0xA7); // goto
+        self.patch_jump(enabled);
+        self.current_bytecode.push(0x03); // iconst_0
+        self.patch_jump(done);
+        self.emit_field_access(0xB3, ASSERTIONS_FIELD, "Z");
+
+        let mut init = std::mem::replace(&mut self.current_bytecode, main);
+        init.append(&mut self.clinit_bytecode);
+        self.clinit_bytecode = init;
+    }
+
+    // Salto con destino pendiente; devuelve la posición del opcode
+    fn emit_jump(&mut self, opcode: u8) -> usize {
+        let pos = self.current_bytecode.len();
+        self.current_bytecode
+            .extend_from_slice(&[opcode, 0x00, 0x00]);
+        pos
+    }
+
+    // Apunta el salto emitido en `pos` a la posición actual
+    fn patch_jump(&mut self, pos: usize) {
+        let offset = (self.current_bytecode.len() - pos) as i16;
+        self.current_bytecode[pos + 1..pos + 3].copy_from_slice(&offset.to_be_bytes());
+    }
+}
@@ -0,0 +1,76 @@
+// src/compiler/codegen/calls.rs
+//
+// Llamadas a funciones Kujav y a extensiones (argumentos ya ordenados, convertidos
+// y con los varargs empaquetados por el análisis semántico) y a métodos estáticos
+// y de instancia de Java con la sobrecarga ya elegida.
+use crate::compiler::calls::jvm_name;
+use crate::compiler::codegen::{Compiler, is_ref_sig};
+use crate::compiler::ir::TExpr;
+use crate::compiler::java_symbols::JavaMethod;
+use crate::compiler::types::KType;
+
+impl Compiler {
+    pub fn compile_call(&mut self, name: &str, args: Vec<TExpr>) {
+        let desc = self.function_desc(&self.functions[name]);
+        for arg in args {
+            self.compile_expression(arg);
+        }
+        let class_name = self.class_name.clone();
+        self.emit_invoke(0xB8, &class_name, jvm_name(name), &desc);
+    }
+
+    // Con receptor: invokevirtual o invokeinterface; sin él, invokestatic
+    pub fn compile_java_call(
+        &mut self,
+        receiver: Option<TExpr>,
+        method: &str,
+        target: &JavaMethod,
+        args: Vec<TExpr>,
+    ) {
+        let is_static = receiver.is_none();
+        if let Some(receiver) = receiver {
+            self.compile_expression(receiver);
+        }
+        for arg in args {
+            self.compile_expression(arg);
+        }
+        if is_static {
+            self.emit_invoke(0xB8, &target.owner, method, &target.descriptor);
+        } else if target.interface {
+            self.emit_invoke_interface(&target.owner, method, &target.descriptor);
+        } else {
+            self.emit_invoke(0xB6, &target.owner, method, &target.descriptor); // invokevirtual
+        }
+    }
+
+    // Tras el código del módulo, el main(String[]) de la JVM llama al `main` del
+    // usuario con los argumentos del programa; un Int devuelto es el código de salida
+    pub fn compile_entry_call(&mut self) {
+        let Some(sig) = self.functions.get("main") else {
+            return;
+        };
+        let (desc, takes_args, exits) = (
+            self.function_desc(sig),
+            !sig.params.is_empty(),
+            sig.ret == KType::Int,
+        );
+        if takes_args {
+            self.current_bytecode.push(0x2A); // aload_0
+        }
+        let class_name = self.class_name.clone();
+        self.emit_invoke(0xB8, &class_name, jvm_name("main"), &desc);
+        if exits {
+            self.emit_invoke(0xB8, "java/lang/System", "exit", "(I)V");
+        }
+    }
+
+    // Conversión implícita que decidió la semántica (ensanchamiento, boxing o unboxing)
+    pub fn compile_converted(&mut self, expr: TExpr, target: &KType) {
+        let sig = self.java.type_sig(&expr.ktype);
+        let target_sig = self.java.type_sig(target);
+        self.compile_expression(expr);
+        if sig != target_sig && !(is_ref_sig(&sig) && is_ref_sig(&target_sig)) {
+            self.emit_cast(&sig, target, false);
+        }
+    }
+}
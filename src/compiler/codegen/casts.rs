@@ -0,0 +1,125 @@
+// src/compiler/codegen/casts.rs
+use crate::compiler::codegen::{Compiler, is_ref_sig};
+use crate::compiler::types::KType;
+
+// (clase envoltorio, nombre del primitivo) para un descriptor primitivo
+fn box_info(sig: &str) -> (&'static str, &'static str) {
+    match sig {
+        "J" => ("java/lang/Long", "long"),
+        "F" => ("java/lang/Float", "float"),
+        "D" => ("java/lang/Double", "double"),
+        "B" => ("java/lang/Byte", "byte"),
+        "S" => ("java/lang/Short", "short"),
+        "C" => ("java/lang/Character", "char"),
+        "Z" => ("java/lang/Boolean", "boolean"),
+        _ => ("java/lang/Integer", "int"),
+    }
+}
+
+// Los tipos menores que int viajan en la pila como int
+fn stack_kind(sig: &str) -> &str {
+    match sig {
+        "J" | "F" | "D" => sig,
+        _ => "I",
+    }
+}
+
+impl Compiler {
+    // Convierte el valor en la cima de la pila (descriptor `from`) al tipo `to`
+    pub fn emit_cast(&mut self, from: &str, to: &KType, safe: bool) {
+        let to_sig = self.java.type_sig(to);
+        match (is_ref_sig(from), is_ref_sig(&to_sig)) {
+            (false, false) => self.emit_primitive_conversion(from, &to_sig),
+            (false, true) => {
+                self.emit_box(from);
+                let target = self.java.class_ref(to);
+                if target != box_info(from).0 {
+                    self.emit_checkcast(&target);
+                }
+            }
+            (true, false) => {
+                let (box_class, _) = box_info(&to_sig);
+                self.emit_checkcast(box_class);
+                self.emit_unbox(&to_sig);
+            }
+            (true, true) => {
+                let target = self.java.class_ref(to);
+                if safe {
+                    self.emit_safe_cast(&target);
+                } else {
+                    self.emit_checkcast(&target);
+                }
+            }
+        }
+    }
+
+    // `expr is T` deja un int 0/1; los primitivos se prueban contra su envoltorio
+    pub fn emit_instanceof(&mut self, to: &KType) {
+        let target = self.java.class_ref(to);
+        let class_idx = self.class_const(&target);
+        self.current_bytecode.push(0xC1);
+        self.current_bytecode
+            .extend_from_slice(&class_idx.to_be_bytes());
+    }
+
+    pub fn emit_checkcast(&mut self, internal_name: &str) {
+        let class_idx = self.class_const(internal_name);
+        self.current_bytecode.push(0xC0);
+        self.current_bytecode
+            .extend_from_slice(&class_idx.to_be_bytes());
+    }
+
+    // dup; instanceof T; ifne L1; pop; aconst_null; goto L2; L1: checkcast T; L2:
+    fn emit_safe_cast(&mut self, internal_name: &str) {
+        let class_idx = self.class_const(internal_name);
+        let idx = class_idx.to_be_bytes();
+        self.current_bytecode.push(0x59);
+        self.current_bytecode
+            .extend_from_slice(&[0xC1, idx[0], idx[1]]);
+        self.current_bytecode.extend_from_slice(&[0x9A, 0x00, 0x08]);
+        self.current_bytecode.extend_from_slice(&[0x57, 0x01]);
+        self.current_bytecode.extend_from_slice(&[0xA7, 0x00, 0x06]);
+        self.current_bytecode
+            .extend_from_slice(&[0xC0, idx[0], idx[1]]);
+    }
+
+    pub fn emit_box(&mut self, sig: &str) {
+        let (class, _) = box_info(sig);
+        self.emit_invoke(0xB8, class, "valueOf", &format!("({})L{};", sig, class));
+    }
+
+    // Espera el envoltorio correcto ya en la pila (tras checkcast)
+    pub fn emit_unbox(&mut self, sig: &str) {
+        let (class, prim) = box_info(sig);
+        self.emit_invoke(0xB6, class, &format!("{prim}Value"), &format!("(){sig}"));
+    }
+
+    pub fn emit_primitive_conversion(&mut self, from: &str, to: &str) {
+        let opcode = match (stack_kind(from), stack_kind(to)) {
+            ("I", "J") => Some(0x85), // i2l
+            ("I", "F") => Some(0x86), // i2f
+            ("I", "D") => Some(0x87), // i2d
+            ("J", "I") => Some(0x88), // l2i
+            ("J", "F") => Some(0x89), // l2f
+            ("J", "D") => Some(0x8A), // l2d
+            ("F", "I") => Some(0x8B), // f2i
+            ("F", "J") => Some(0x8C), // f2l
+            ("F", "D") => Some(0x8D), // f2d
+            ("D", "I") => Some(0x8E), // d2i
+            ("D", "J") => Some(0x8F), // d2l
+            ("D", "F") => Some(0x90), // d2f
+            _ => None,
+        };
+        if let Some(op) = opcode {
+            self.current_bytecode.push(op);
+        }
+        if from != to {
+            match to {
+                "B" => self.current_bytecode.push(0x91), // i2b
+                "C" => self.current_bytecode.push(0x92), // i2c
+                "S" => self.current_bytecode.push(0x93), // i2s
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,198 @@
+// src/compiler/codegen/collections.rs
+//
+// List/Set/Map respaldados por java.util: literales, índices y `for ... in`.
+use crate::compiler::codegen::{Compiler, array_load_op, array_store_op, is_ref_sig, store_op};
+use crate::compiler::ir::{TExpr, TExprKind, TStmt, VarId};
+use crate::compiler::types::KType;
+
+const OBJECT_SIG: &str = "Ljava/lang/Object;";
+
+impl Compiler {
+    // `[1, 2]` declarado como List o Set: ArrayList/LinkedHashSet con los elementos envueltos
+    pub fn compile_collection_literal(&mut self, ktype: &KType, elems: Vec<TExpr>) {
+        let (_, class) = ktype.collection_classes().expect("literal de List o Set");
+        self.emit_new_collection(class);
+        for e in elems {
+            self.current_bytecode.push(0x59); // dup
+            self.compile_boxed(e);
+            self.emit_invoke_interface("java/util/Collection", "add", "(Ljava/lang/Object;)Z");
+            self.current_bytecode.push(0x57); // pop
+        }
+    }
+
+    pub fn compile_map_literal(&mut self, entries: Vec<(TExpr, TExpr)>) {
+        self.emit_new_collection("java/util/LinkedHashMap");
+        for (k, v) in entries {
+            self.current_bytecode.push(0x59);
+            self.compile_boxed(k);
+            self.compile_boxed(v);
+            self.emit_invoke_interface(
+                "java/util/Map",
+                "put",
+                "(Ljava/lang/Object;Ljava/lang/Object;)Ljava/lang/Object;",
+            );
+            self.current_bytecode.push(0x57);
+        }
+    }
+
+    fn emit_new_collection(&mut self, class: &str) {
+        let class_idx = self.class_const(class);
+        self.current_bytecode.push(0xBB); // new
+        self.current_bytecode
+            .extend_from_slice(&class_idx.to_be_bytes());
+        self.current_bytecode.push(0x59);
+        self.emit_invoke(0xB7, class, "<init>", "()V");
+    }
+
+    // Los primitivos viajan como Integer/Boolean/... dentro de las colecciones
+    pub fn compile_boxed(&mut self, expr: TExpr) {
+        let sig = self.java.type_sig(&expr.ktype);
+        self.compile_expression(expr);
+        if !is_ref_sig(&sig) {
+            self.emit_box(&sig);
+        }
+    }
+
+    // nombre[idx] para arreglos, listas (get) y mapas (get + unboxing del valor)
+    pub fn compile_index_get(&mut self, container: TExpr, idx: TExpr) {
+        let container_t = container.ktype.clone();
+        self.compile_expression(container);
+        match container_t {
+            KType::List(elem) => {
+                self.compile_expression(idx);
+                self.emit_invoke_interface("java/util/List", "get", "(I)Ljava/lang/Object;");
+                self.emit_cast(OBJECT_SIG, &elem, false);
+            }
+            KType::Map(_, value) => {
+                self.compile_boxed(idx);
+                self.emit_invoke_interface(
+                    "java/util/Map",
+                    "get",
+                    "(Ljava/lang/Object;)Ljava/lang/Object;",
+                );
+                self.emit_cast(OBJECT_SIG, &value, false);
+            }
+            other => {
+                let elem_sig = match other {
+                    KType::Array(elem) => self.java.type_sig(&elem),
+                    _ => "I".into(),
+                };
+                self.compile_expression(idx);
+                self.current_bytecode.push(array_load_op(&elem_sig));
+            }
+        }
+    }
+
+    pub fn compile_index_set(&mut self, container: TExpr, idx: TExpr, val: TExpr) {
+        let container_t = container.ktype.clone();
+        self.compile_expression(container);
+        match container_t {
+            KType::List(_) => {
+                self.compile_expression(idx);
+                self.compile_boxed(val);
+                self.emit_invoke_interface(
+                    "java/util/List",
+                    "set",
+                    "(ILjava/lang/Object;)Ljava/lang/Object;",
+                );
+                self.current_bytecode.push(0x57);
+            }
+            KType::Map(_, _) => {
+                self.compile_boxed(idx);
+                self.compile_boxed(val);
+                self.emit_invoke_interface(
+                    "java/util/Map",
+                    "put",
+                    "(Ljava/lang/Object;Ljava/lang/Object;)Ljava/lang/Object;",
+                );
+                self.current_bytecode.push(0x57);
+            }
+            other => {
+                let elem_sig = match other {
+                    KType::Array(elem) => self.java.type_sig(&elem),
+                    _ => "I".into(),
+                };
+                self.compile_expression(idx);
+                self.compile_expression(val);
+                self.current_bytecode.push(array_store_op(&elem_sig));
+            }
+        }
+    }
+
+    // Rangos con un contador; arreglos por índice; List/Set con su Iterator; Map recorre keySet()
+    pub fn compile_for(&mut self, var: VarId, iterable: TExpr, body: Vec<TStmt>) {
+        let iter_t = iterable.ktype.clone();
+        let elem_t = match &iter_t {
+            KType::Array(elem) | KType::List(elem) | KType::Set(elem) | KType::Map(elem, _) => {
+                (**elem).clone()
+            }
+            _ => unreachable!("`for` validado en el análisis semántico"),
+        };
+        let elem_sig = self.java.type_sig(&elem_t);
+        // El ámbito del `for` contiene los temporales y la variable del bucle
+        self.push_scope();
+        let (start_pos, jump_pos, counter) = match iterable {
+            TExpr {
+                kind: TExprKind::Range(start, end, inclusive),
+                ..
+            } => self.compile_range_head(*start, *end, inclusive),
+            iterable if matches!(iter_t, KType::Array(_)) => {
+                self.compile_expression(iterable);
+                let (array_slot, index_slot) = (self.alloc_slot(1), self.alloc_slot(1));
+                self.current_bytecode.extend_from_slice(&[0x3A, array_slot]); // astore
+                self.current_bytecode
+                    .extend_from_slice(&[0x03, 0x36, index_slot]); // iconst_0; istore
+                let start_pos = self.current_bytecode.len();
+                self.current_bytecode
+                    .extend_from_slice(&[0x15, index_slot, 0x19, array_slot, 0xBE]);
+                let jump_pos = self.current_bytecode.len();
+                self.current_bytecode.extend_from_slice(&[0xA2, 0x00, 0x00]); // if_icmpge fin
+                self.current_bytecode
+                    .extend_from_slice(&[0x19, array_slot, 0x15, index_slot]);
+                self.current_bytecode.push(array_load_op(&elem_sig));
+                (start_pos, jump_pos, Some(index_slot))
+            }
+            iterable => {
+                self.compile_expression(iterable);
+                if let KType::Map(_, _) = iter_t {
+                    self.emit_invoke_interface("java/util/Map", "keySet", "()Ljava/util/Set;");
+                }
+                self.emit_invoke_interface(
+                    "java/lang/Iterable",
+                    "iterator",
+                    "()Ljava/util/Iterator;",
+                );
+                let iter_slot = self.alloc_slot(1);
+                self.current_bytecode.extend_from_slice(&[0x3A, iter_slot]);
+                let start_pos = self.current_bytecode.len();
+                self.current_bytecode.extend_from_slice(&[0x19, iter_slot]);
+                self.emit_invoke_interface("java/util/Iterator", "hasNext", "()Z");
+                let jump_pos = self.current_bytecode.len();
+                self.current_bytecode.extend_from_slice(&[0x99, 0x00, 0x00]); // ifeq fin
+                self.current_bytecode.extend_from_slice(&[0x19, iter_slot]);
+                self.emit_invoke_interface("java/util/Iterator", "next", "()Ljava/lang/Object;");
+                self.emit_cast(OBJECT_SIG, &elem_t, false);
+                (start_pos, jump_pos, None)
+            }
+        };
+
+        let var_slot = self.declare_local(var, &elem_t);
+        self.current_bytecode
+            .extend_from_slice(&[store_op(&elem_sig), var_slot]);
+        self.compile_block(body);
+
+        if let Some(index_slot) = counter {
+            self.current_bytecode
+                .extend_from_slice(&[0x84, index_slot, 1]); // iinc
+        }
+        let goto_pos = self.current_bytecode.len();
+        self.current_bytecode.push(0xA7);
+        let off_start = (start_pos as i32 - goto_pos as i32) as i16;
+        self.current_bytecode
+            .extend_from_slice(&off_start.to_be_bytes());
+        let off_end = (self.current_bytecode.len() - jump_pos) as i16;
+        self.current_bytecode[jump_pos + 1..jump_pos + 3].copy_from_slice(&off_end.to_be_bytes());
+
+        self.pop_scope();
+    }
+}
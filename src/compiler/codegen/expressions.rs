@@ -1,151 +1,66 @@
 // src/compiler/codegen/expressions.rs
-use crate::compiler::codegen::Compiler;
-use crate::parser::ast::Expr;
+use crate::compiler::codegen::{Compiler, is_ref_sig, load_op};
+use crate::compiler::ir::{TExpr, TExprKind};
 
 impl Compiler {
-    pub fn is_ref_expr(&self, expr: &Expr) -> bool {
-        match expr {
-            Expr::String(_) | Expr::ArrayLiteral(_) => true,
-            Expr::Identifier(n) => {
-                if let Some(t) = self.variable_types.get(n) {
-                    t.starts_with('L') || t.starts_with('[')
-                } else {
-                    false
-                }
-            }
-            Expr::ArrayAccess(_, _) => false,
-            Expr::Binary(l, op, r) => op == "+" && (self.is_ref_expr(l) || self.is_ref_expr(r)),
-            _ => false,
-        }
-    }
-
-    pub fn compile_expression(&mut self, expr: Expr) {
-        match expr {
-            Expr::Number(val) => {
-                self.current_bytecode.push(0x10);
-                self.current_bytecode.push(val as u8);
-            }
-            Expr::Boolean(val) => {
+    pub fn compile_expression(&mut self, expr: TExpr) {
+        let TExpr { kind, ktype } = expr;
+        match kind {
+            TExprKind::Number(val) => self.emit_int(val),
+            TExprKind::Boolean(val) => {
                 self.current_bytecode.push(if val { 0x04 } else { 0x03 });
             }
-            Expr::String(c) => {
+            TExprKind::String(c) => {
                 let u_idx = self.cp.add_utf8(&c);
                 let s_idx = self.cp.add_string(u_idx);
-                self.current_bytecode.push(0x12);
-                self.current_bytecode.push(s_idx as u8);
+                self.emit_ldc(s_idx);
             }
-            Expr::Identifier(n) => {
-                if let Some(&slot) = self.variables.get(&n) {
-                    let is_ref = self.is_ref_expr(&Expr::Identifier(n.clone()));
-                    self.current_bytecode.push(if is_ref { 0x19 } else { 0x15 });
-                    self.current_bytecode.push(slot);
-                }
+            TExprKind::Const(value) => self.emit_const_value(&value),
+            TExprKind::Local(id) => {
+                let Some(&slot) = self.locals.get(&id) else {
+                    panic!(
+                        "error interno del compilador: la local {} no tiene slot",
+                        id
+                    );
+                };
+                let sig = self.java.type_sig(&ktype);
+                self.current_bytecode
+                    .extend_from_slice(&[load_op(&sig), slot]);
             }
-            Expr::ArrayLiteral(elems) => {
-                let size = elems.len() as u8;
-                self.current_bytecode.push(0x10);
-                self.current_bytecode.push(size);
-                self.current_bytecode.push(0xBC);
-                self.current_bytecode.push(10);
-                for (i, e) in elems.into_iter().enumerate() {
-                    self.current_bytecode.push(0x59);
-                    self.current_bytecode.push(0x10);
-                    self.current_bytecode.push(i as u8);
-                    self.compile_expression(e);
-                    self.current_bytecode.push(0x4F);
-                }
+            TExprKind::Global(name) => {
+                let sig = self.java.type_sig(&ktype);
+                self.emit_field_access(0xB2, &name, &sig); // getstatic
             }
-            Expr::ArrayAccess(name, idx) => {
-                if let Some(&slot) = self.variables.get(&name) {
-                    self.current_bytecode.push(0x19);
-                    self.current_bytecode.push(slot);
-                    self.compile_expression(*idx);
-                    self.current_bytecode.push(0x2E);
-                }
+            TExprKind::Convert(inner) => self.compile_converted(*inner, &ktype),
+            TExprKind::Cast(inner, safe) => {
+                let from = self.java.type_sig(&inner.ktype);
+                self.compile_expression(*inner);
+                self.emit_cast(&from, &ktype, safe);
             }
-            Expr::Binary(l, op, r) => {
-                if op == "+" && (self.is_ref_expr(&l) || self.is_ref_expr(&r)) {
-                    let sb_u = self.cp.add_utf8("java/lang/StringBuilder");
-                    let sb_c = self.cp.add_class(sb_u);
-                    self.current_bytecode.push(0xBB);
-                    self.current_bytecode.extend_from_slice(&sb_c.to_be_bytes());
-                    self.current_bytecode.push(0x59);
-
-                    let init_n = self.cp.add_utf8("<init>");
-                    let init_s = self.cp.add_utf8("()V");
-                    let nt_init = self.cp.add_name_and_type(init_n, init_s);
-                    let m_init = self.cp.add_method_ref(sb_c, nt_init);
-                    self.current_bytecode.push(0xB7);
-                    self.current_bytecode
-                        .extend_from_slice(&m_init.to_be_bytes());
-
-                    let l_is_ref = self.is_ref_expr(&l);
-                    self.compile_expression(*l);
-                    let sig_l = if l_is_ref {
-                        "(Ljava/lang/Object;)Ljava/lang/StringBuilder;"
-                    } else {
-                        "(I)Ljava/lang/StringBuilder;"
-                    };
-                    let app_n = self.cp.add_utf8("append");
-                    let sig_l_u = self.cp.add_utf8(sig_l);
-                    let nt_l = self.cp.add_name_and_type(app_n, sig_l_u);
-                    let m_app_l = self.cp.add_method_ref(sb_c, nt_l);
-                    self.current_bytecode.push(0xB6);
-                    self.current_bytecode
-                        .extend_from_slice(&m_app_l.to_be_bytes());
-
-                    let r_is_ref = self.is_ref_expr(&r);
-                    self.compile_expression(*r);
-                    let sig_r = if r_is_ref {
-                        "(Ljava/lang/Object;)Ljava/lang/StringBuilder;"
-                    } else {
-                        "(I)Ljava/lang/StringBuilder;"
-                    };
-                    let sig_r_u = self.cp.add_utf8(sig_r);
-                    let nt_r = self.cp.add_name_and_type(app_n, sig_r_u);
-                    let m_app_r = self.cp.add_method_ref(sb_c, nt_r);
-                    self.current_bytecode.push(0xB6);
-                    self.current_bytecode
-                        .extend_from_slice(&m_app_r.to_be_bytes());
-
-                    let ts_n = self.cp.add_utf8("toString");
-                    let ts_s = self.cp.add_utf8("()Ljava/lang/String;");
-                    let nt_ts = self.cp.add_name_and_type(ts_n, ts_s);
-                    let m_ts = self.cp.add_method_ref(sb_c, nt_ts);
-                    self.current_bytecode.push(0xB6);
-                    self.current_bytecode.extend_from_slice(&m_ts.to_be_bytes());
-                } else {
-                    self.compile_expression(*l);
-                    self.compile_expression(*r);
-                    match op.as_str() {
-                        "+" => self.current_bytecode.push(0x60),
-                        "-" => self.current_bytecode.push(0x64),
-                        "*" => self.current_bytecode.push(0x68),
-                        "/" => self.current_bytecode.push(0x6C),
-                        "==" => self
-                            .current_bytecode
-                            .extend_from_slice(&[0xA0, 0x00, 0x07, 0x04, 0xA7, 0x00, 0x04, 0x03]),
-                        "and" => self.current_bytecode.push(0x7E),
-                        "or" => self.current_bytecode.push(0x80),
-                        _ => {}
-                    }
+            TExprKind::TypeTest(inner, target) => {
+                let from = self.java.type_sig(&inner.ktype);
+                self.compile_expression(*inner);
+                if !is_ref_sig(&from) {
+                    self.emit_box(&from);
                 }
+                self.emit_instanceof(&target);
             }
-            Expr::Call(name, args) => {
-                for arg in args {
-                    self.compile_expression(arg);
-                }
-                let sal_u = self.cp.add_utf8("Salida");
-                let cls = self.cp.add_class(sal_u);
-                let call_n = self.cp.add_utf8(&name);
-                let call_s = self.cp.add_utf8("()I");
-                let nt = self.cp.add_name_and_type(call_n, call_s);
-                let m_ref = self.cp.add_method_ref(cls, nt);
-                self.current_bytecode.push(0xB8);
-                self.current_bytecode
-                    .extend_from_slice(&m_ref.to_be_bytes());
+            TExprKind::ArrayLiteral(elems) => self.compile_array_literal(&ktype, elems),
+            TExprKind::NewArray(dims) => self.compile_new_array(&ktype, dims),
+            TExprKind::Length(inner) => self.compile_length(*inner),
+            TExprKind::MapLiteral(entries) => self.compile_map_literal(entries),
+            TExprKind::Index(container, idx) => self.compile_index_get(*container, *idx),
+            TExprKind::Slice(container, name, range) => {
+                self.compile_slice(*container, &name, *range)
+            }
+            TExprKind::Range(_, _, _) => unreachable!("rango fuera de un for o un slice"),
+            TExprKind::Binary(l, op, r) => self.compile_binary(*l, &op, *r, &ktype),
+            TExprKind::Call(name, args) => self.compile_call(&name, args),
+            TExprKind::JavaCall(receiver, method, target, args) => {
+                self.compile_java_call(receiver.map(|r| *r), &method, &target, args)
             }
-            Expr::Input => {
+            TExprKind::Tuple(elems) => self.compile_tuple(elems),
+            TExprKind::Input => {
                 let sc_u = self.cp.add_utf8("java/util/Scanner");
                 let scan_c = self.cp.add_class(sc_u);
                 self.current_bytecode.push(0xBB);
@@ -0,0 +1,100 @@
+// src/compiler/codegen/globals.rs
+//
+// Constantes (`const`) y globales (`local` del nivel superior) como campos estáticos.
+use crate::compiler::codegen::{Compiler, FieldInfo, access_flags};
+use crate::compiler::constants::ConstValue;
+use crate::compiler::types::KType;
+use crate::parser::ast::{Annotation, Visibility};
+
+const ACC_FINAL: u16 = 0x0010;
+
+impl Compiler {
+    // static final con ConstantValue; los usos ya llevan el valor sustituido
+    pub fn compile_const(
+        &mut self,
+        name: String,
+        value: ConstValue,
+        annotations: Vec<Annotation>,
+        visibility: Option<Visibility>,
+    ) {
+        let sig = self.java.type_sig(&value.ktype());
+        let const_idx = match &value {
+            ConstValue::Int(v) => self.cp.add_integer(*v),
+            ConstValue::Bool(v) => self.cp.add_integer(*v as i32),
+            ConstValue::String(v) => {
+                let u_idx = self.cp.add_utf8(v);
+                self.cp.add_string(u_idx)
+            }
+        };
+        let mut attributes = vec![(
+            self.cp.add_utf8("ConstantValue"),
+            const_idx.to_be_bytes().to_vec(),
+        )];
+        attributes.extend(self.annotation_attributes(&annotations, "FIELD", &[]));
+        self.fields.push(FieldInfo {
+            access_flags: access_flags(visibility) | ACC_FINAL,
+            name_idx: self.cp.add_utf8(&name),
+            sig_idx: self.cp.add_utf8(&sig),
+            attributes,
+        });
+    }
+
+    // Campo static; su valor lo asigna un StaticInit en <clinit> o el código de main
+    pub fn declare_global(
+        &mut self,
+        name: &str,
+        ktype: &KType,
+        annotations: &[Annotation],
+        visibility: Option<Visibility>,
+    ) {
+        let sig = self.java.type_sig(ktype);
+        let attributes = self.annotation_attributes(annotations, "FIELD", &[]);
+        self.fields.push(FieldInfo {
+            access_flags: access_flags(visibility),
+            name_idx: self.cp.add_utf8(name),
+            sig_idx: self.cp.add_utf8(&sig),
+            attributes,
+        });
+    }
+
+    // Redirige la emisión al cuerpo de <clinit>; devuelve el bytecode de main
+    // Los temporales de <clinit> (p. ej. los de un slice) usan sus propios slots
+    pub fn enter_clinit(&mut self) -> (Vec<u8>, u8) {
+        let clinit = std::mem::take(&mut self.clinit_bytecode);
+        let main_slot = std::mem::replace(&mut self.next_slot, self.clinit_locals);
+        (
+            std::mem::replace(&mut self.current_bytecode, clinit),
+            main_slot,
+        )
+    }
+
+    pub fn leave_clinit(&mut self, (main_bc, main_slot): (Vec<u8>, u8)) {
+        self.clinit_bytecode = std::mem::replace(&mut self.current_bytecode, main_bc);
+        self.clinit_locals = std::mem::replace(&mut self.next_slot, main_slot);
+    }
+
+    // getstatic (0xB2) / putstatic (0xB3) sobre un campo de la clase que se compila
+    pub fn emit_field_access(&mut self, opcode: u8, name: &str, sig: &str) {
+        let class_name = self.class_name.clone();
+        let class_idx = self.class_const(&class_name);
+        let name_idx = self.cp.add_utf8(name);
+        let sig_idx = self.cp.add_utf8(sig);
+        let nt = self.cp.add_name_and_type(name_idx, sig_idx);
+        let f_ref = self.cp.add_field_ref(class_idx, nt);
+        self.current_bytecode.push(opcode);
+        self.current_bytecode
+            .extend_from_slice(&f_ref.to_be_bytes());
+    }
+
+    pub fn emit_const_value(&mut self, value: &ConstValue) {
+        match value {
+            ConstValue::Int(v) => self.emit_int(*v),
+            ConstValue::Bool(v) => self.emit_int(*v as i32),
+            ConstValue::String(v) => {
+                let u_idx = self.cp.add_utf8(v);
+                let s_idx = self.cp.add_string(u_idx);
+                self.emit_ldc(s_idx);
+            }
+        }
+    }
+}
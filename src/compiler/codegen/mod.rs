@@ -1,35 +1,242 @@
 // src/compiler/codegen/mod.rs
+pub mod annotations;
+pub mod arrays;
+pub mod assertions;
+pub mod calls;
+pub mod casts;
+pub mod collections;
 pub mod expressions;
+pub mod globals;
+pub mod operators;
+pub mod ranges;
+pub mod scopes;
+pub mod stack;
 pub mod statements;
+pub mod tuples;
 
+use crate::compiler::calls::FunctionSig;
+use crate::compiler::ir::VarId;
+use crate::compiler::java_symbols::JavaSymbols;
 use crate::core::constant_pool::ConstantPool;
+use crate::parser::ast::Visibility;
 use std::collections::HashMap;
 
 pub struct MethodInfo {
+    pub access_flags: u16,
     pub name_idx: u16,
     pub sig_idx: u16,
     pub bytecode: Vec<u8>,
+    pub max_stack: u16,
     pub max_locals: u16,
+    pub attributes: Vec<(u16, Vec<u8>)>, // Atributos además de Code: (nombre, contenido)
+}
+
+pub struct FieldInfo {
+    pub access_flags: u16,
+    pub name_idx: u16,
+    pub sig_idx: u16,
+    pub attributes: Vec<(u16, Vec<u8>)>,
 }
 
 pub struct Compiler {
     pub cp: ConstantPool,
+    pub java: JavaSymbols,
+    pub class_name: String,
     pub methods: Vec<MethodInfo>,
+    pub fields: Vec<FieldInfo>,
     pub current_bytecode: Vec<u8>,
-    pub variables: HashMap<String, u8>,
-    pub variable_types: HashMap<String, String>,
+    pub clinit_bytecode: Vec<u8>,
+    pub clinit_locals: u8,
+    pub locals: HashMap<VarId, u8>, // Slot de cada local del método que se compila
+    pub functions: HashMap<String, FunctionSig>, // Firmas validadas por el análisis semántico
     pub next_slot: u8,
+    pub max_slot: u8, // Mayor next_slot alcanzado: max_locals del método
+    pub scopes: Vec<scopes::CodeScope>, // Bloques abiertos, del más externo al actual
+    pub uses_range_check: bool, // Algún slice necesita el método auxiliar kujav$checkRange
+    pub uses_assertions: bool, // Algún assert necesita el campo $assertionsDisabled
 }
 
 impl Compiler {
-    pub fn new() -> Self {
+    pub fn new(
+        class_name: &str,
+        java: JavaSymbols,
+        functions: HashMap<String, FunctionSig>,
+    ) -> Self {
         Self {
             cp: ConstantPool::new(),
+            java,
+            class_name: class_name.to_string(),
             methods: Vec::new(),
+            fields: Vec::new(),
             current_bytecode: Vec::new(),
-            variables: HashMap::new(),
-            variable_types: HashMap::new(),
+            clinit_bytecode: Vec::new(),
+            clinit_locals: 0,
+            locals: HashMap::new(),
+            functions,
             next_slot: 1,
+            max_slot: 1,
+            scopes: Vec::new(),
+            uses_range_check: false,
+            uses_assertions: false,
+        }
+    }
+
+    pub fn class_const(&mut self, internal_name: &str) -> u16 {
+        let name_idx = self.cp.add_utf8(internal_name);
+        self.cp.add_class(name_idx)
+    }
+
+    // invokestatic/invokevirtual/... sobre `class.name:desc`
+    pub fn emit_invoke(&mut self, opcode: u8, class: &str, name: &str, desc: &str) {
+        let class_idx = self.class_const(class);
+        let name_idx = self.cp.add_utf8(name);
+        let desc_idx = self.cp.add_utf8(desc);
+        let nt = self.cp.add_name_and_type(name_idx, desc_idx);
+        let m_ref = self.cp.add_method_ref(class_idx, nt);
+        self.current_bytecode.push(opcode);
+        self.current_bytecode
+            .extend_from_slice(&m_ref.to_be_bytes());
+    }
+
+    pub fn function_desc(&self, sig: &FunctionSig) -> String {
+        let params: String = sig
+            .params
+            .iter()
+            .map(|(_, t, _)| self.java.type_sig(t))
+            .collect();
+        format!("({}){}", params, self.java.type_sig(&sig.ret))
+    }
+
+    // invokeinterface: el byte `count` incluye el receptor
+    pub fn emit_invoke_interface(&mut self, class: &str, name: &str, desc: &str) {
+        let class_idx = self.class_const(class);
+        let name_idx = self.cp.add_utf8(name);
+        let desc_idx = self.cp.add_utf8(desc);
+        let nt = self.cp.add_name_and_type(name_idx, desc_idx);
+        let m_ref = self.cp.add_interface_method_ref(class_idx, nt);
+        self.current_bytecode.push(0xB9);
+        self.current_bytecode
+            .extend_from_slice(&m_ref.to_be_bytes());
+        self.current_bytecode.push(1 + arg_slots(desc));
+        self.current_bytecode.push(0);
+    }
+
+    // Constante entera con la instrucción más corta: iconst_<n>, bipush, sipush o ldc
+    pub fn emit_int(&mut self, value: i32) {
+        match value {
+            -1..=5 => self.current_bytecode.push((0x03 + value) as u8),
+            -128..=127 => self
+                .current_bytecode
+                .extend_from_slice(&[0x10, value as u8]),
+            -32768..=32767 => {
+                self.current_bytecode.push(0x11);
+                self.current_bytecode
+                    .extend_from_slice(&(value as i16).to_be_bytes());
+            }
+            _ => {
+                let idx = self.cp.add_integer(value);
+                self.emit_ldc(idx);
+            }
+        }
+    }
+
+    pub fn emit_ldc(&mut self, idx: u16) {
+        if idx <= u8::MAX as u16 {
+            self.current_bytecode.extend_from_slice(&[0x12, idx as u8]);
+        } else {
+            self.current_bytecode.push(0x13); // ldc_w
+            self.current_bytecode.extend_from_slice(&idx.to_be_bytes());
+        }
+    }
+}
+
+// public static / private static / static (package-private) según el modificador
+pub fn access_flags(visibility: Option<Visibility>) -> u16 {
+    match visibility {
+        None | Some(Visibility::Pub) => 0x0009,
+        Some(Visibility::Private) => 0x000A,
+        Some(Visibility::Internal) => 0x0008,
+    }
+}
+
+// Opcodes por descriptor JVM: int (y tipos menores), long, float, double o referencia
+pub fn load_op(sig: &str) -> u8 {
+    match sig {
+        "J" => 0x16,
+        "F" => 0x17,
+        "D" => 0x18,
+        s if is_ref_sig(s) => 0x19,
+        _ => 0x15,
+    }
+}
+
+// xaload según el descriptor del elemento
+pub fn array_load_op(elem_sig: &str) -> u8 {
+    match elem_sig {
+        "J" => 0x2F,
+        "F" => 0x30,
+        "D" => 0x31,
+        "Z" | "B" => 0x33,
+        "C" => 0x34,
+        "S" => 0x35,
+        s if is_ref_sig(s) => 0x32,
+        _ => 0x2E,
+    }
+}
+
+// xastore según el descriptor del elemento
+pub fn array_store_op(elem_sig: &str) -> u8 {
+    array_load_op(elem_sig) + 0x21
+}
+
+pub fn store_op(sig: &str) -> u8 {
+    match sig {
+        "J" => 0x37,
+        "F" => 0x38,
+        "D" => 0x39,
+        s if is_ref_sig(s) => 0x3A,
+        _ => 0x36,
+    }
+}
+
+pub fn return_op(sig: &str) -> u8 {
+    match sig {
+        "J" => 0xAD,
+        "F" => 0xAE,
+        "D" => 0xAF,
+        s if is_ref_sig(s) => 0xB0,
+        _ => 0xAC,
+    }
+}
+
+pub fn is_ref_sig(sig: &str) -> bool {
+    sig.starts_with('L') || sig.starts_with('[')
+}
+
+// Slots que ocupan los argumentos de un descriptor de método "(IJLjava/lang/String;)V"
+pub fn arg_slots(desc: &str) -> u8 {
+    let params = &desc[1..desc.find(')').unwrap_or(1)];
+    let mut slots = 0;
+    let mut chars = params.chars();
+    while let Some(c) = chars.next() {
+        let mut c = c;
+        let is_array = c == '[';
+        while c == '[' {
+            c = chars.next().unwrap_or('I');
+        }
+        if c == 'L' {
+            chars.by_ref().find(|&c| c == ';');
         }
+        slots += if !is_array && (c == 'J' || c == 'D') {
+            2
+        } else {
+            1
+        };
     }
+    slots
+}
+
+// long y double ocupan dos slots de variables locales
+pub fn slot_size(sig: &str) -> u8 {
+    if sig == "J" || sig == "D" { 2 } else { 1 }
 }
@@ -0,0 +1,156 @@
+// src/compiler/codegen/operators.rs
+//
+// Operadores binarios: aritmética con promoción numérica, comparaciones y `+` de String.
+use crate::compiler::codegen::{Compiler, is_ref_sig};
+use crate::compiler::ir::TExpr;
+use crate::compiler::types::KType;
+
+impl Compiler {
+    // Los operandos numéricos ya llegan convertidos al tipo común
+    pub fn compile_binary(&mut self, l: TExpr, op: &str, r: TExpr, ktype: &KType) {
+        if op == "+" && *ktype == KType::String {
+            return self.compile_concat(l, r);
+        }
+        match KType::promote(&l.ktype, &r.ktype) {
+            Some(t) => {
+                let sig = self.java.type_sig(&t);
+                self.compile_expression(l);
+                self.compile_expression(r);
+                match op {
+                    "+" | "-" | "*" | "/" => {
+                        // iadd, isub, imul, idiv y sus variantes l/f/d a +1, +2, +3
+                        let base = match op {
+                            "+" => 0x60,
+                            "-" => 0x64,
+                            "*" => 0x68,
+                            _ => 0x6C,
+                        };
+                        let offset = match sig.as_str() {
+                            "J" => 1,
+                            "F" => 2,
+                            "D" => 3,
+                            _ => 0,
+                        };
+                        self.current_bytecode.push(base + offset);
+                    }
+                    _ => self.emit_compare(&sig, op),
+                }
+            }
+            // Referencias: igualdad de valor con Objects.equals (admite null)
+            None if l.ktype.is_reference() => {
+                self.compile_expression(l);
+                self.compile_expression(r);
+                self.emit_invoke(
+                    0xB8,
+                    "java/util/Objects",
+                    "equals",
+                    "(Ljava/lang/Object;Ljava/lang/Object;)Z",
+                );
+                if op == "!=" {
+                    self.current_bytecode.extend_from_slice(&[0x04, 0x82]); // iconst_1, ixor
+                }
+            }
+            // Bool == Bool se compara como int
+            None => {
+                self.compile_expression(l);
+                self.compile_expression(r);
+                self.emit_compare("Z", op);
+            }
+        }
+    }
+
+    // Deja 1 o 0 en la pila: salta a iconst_0 con la condición contraria
+    fn emit_compare(&mut self, sig: &str, op: &str) {
+        // if_icmpne, if_icmpeq, if_icmpge, if_icmplt, if_icmple, if_icmpgt
+        let negated_icmp: u8 = match op {
+            "==" => 0xA0,
+            "!=" => 0x9F,
+            "<" => 0xA2,
+            ">=" => 0xA1,
+            ">" => 0xA4,
+            _ => 0xA3, // <=
+        };
+        // Con NaN, fcmpg/dcmpg dan 1 y fcmpl/dcmpl -1: ambos hacen falsa la comparación
+        let cmp = match (sig, op) {
+            ("J", _) => Some(0x94),          // lcmp
+            ("F", "<" | "<=") => Some(0x96), // fcmpg
+            ("F", _) => Some(0x95),          // fcmpl
+            ("D", "<" | "<=") => Some(0x98), // dcmpg
+            ("D", _) => Some(0x97),          // dcmpl
+            _ => None,
+        };
+        let jump = match cmp {
+            Some(cmp) => {
+                self.current_bytecode.push(cmp);
+                negated_icmp - 6 // ifne, ifeq, ifge, iflt, ifle, ifgt
+            }
+            None => negated_icmp,
+        };
+        self.current_bytecode
+            .extend_from_slice(&[jump, 0x00, 0x07, 0x04, 0xA7, 0x00, 0x04, 0x03]);
+    }
+
+    // new StringBuilder().append(l).append(r).toString()
+    fn compile_concat(&mut self, l: TExpr, r: TExpr) {
+        let sb_c = self.class_const("java/lang/StringBuilder");
+        self.current_bytecode.push(0xBB);
+        self.current_bytecode.extend_from_slice(&sb_c.to_be_bytes());
+        self.current_bytecode.push(0x59);
+        self.emit_invoke(0xB7, "java/lang/StringBuilder", "<init>", "()V");
+        for operand in [l, r] {
+            let sig = self.java.type_sig(&operand.ktype);
+            self.compile_expression(operand);
+            // append(char) imprime el carácter y append(long) no trunca: una sobrecarga por tipo
+            let param = match sig.as_str() {
+                "Ljava/lang/String;" => "Ljava/lang/String;",
+                s if is_ref_sig(s) => "Ljava/lang/Object;",
+                "B" | "S" | "I" => "I",
+                s => s,
+            };
+            let desc = format!("({})Ljava/lang/StringBuilder;", param);
+            self.emit_invoke(0xB6, "java/lang/StringBuilder", "append", &desc);
+        }
+        self.emit_invoke(
+            0xB6,
+            "java/lang/StringBuilder",
+            "toString",
+            "()Ljava/lang/String;",
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::semantics::SemanticAnalyzer;
+    use crate::parser;
+    use crate::reader::class_index::ClassIndex;
+
+    fn analyze(source: &str) -> Result<(), String> {
+        let mut analyzer = SemanticAnalyzer::new(ClassIndex::new());
+        analyzer.analyze(&parser::parse_to_ast(source)).map(|_| ())
+    }
+
+    #[test]
+    fn plus_with_a_string_on_either_side_concatenates() {
+        let source = "\
+local n = 1
+local a: String = \"a\" + n
+local b: String = n + \"b\"
+local c: String = n + 2 + \"c\"
+local d: String = \"d\" + true
+";
+        assert_eq!(analyze(source), Ok(()));
+        let err = analyze("local s = \"a\"\nlocal n: Int = s + 1\n").unwrap_err();
+        assert!(err.contains("String"), "{}", err);
+    }
+
+    #[test]
+    fn other_operators_reject_string_operands() {
+        for op in ["-", "*", "/", "<"] {
+            let source = format!("local s = \"a\"\nprint s {} 1\n", op);
+            let err = analyze(&source).unwrap_err();
+            let expected = format!("El operador '{}' no se puede aplicar a String y Int", op);
+            assert!(err.contains(&expected), "{}", err);
+        }
+    }
+}
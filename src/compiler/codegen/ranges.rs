@@ -0,0 +1,184 @@
+// src/compiler/codegen/ranges.rs
+//
+// Rangos `a..b` / `a..<b` en `for` y slices `nombre[a..b]` de arreglos y String.
+use crate::compiler::codegen::{Compiler, MethodInfo, is_ref_sig};
+use crate::compiler::ir::{TExpr, TExprKind};
+use crate::compiler::types::KType;
+
+const CHECK_RANGE: &str = "kujav$checkRange";
+const CHECK_RANGE_DESC: &str = "(IIILjava/lang/String;)V";
+
+impl Compiler {
+    // Cabecera del `for` sobre un rango: contador y fin en slots propios, para que
+    // redeclarar la variable del bucle en el cuerpo no altere el recorrido
+    pub fn compile_range_head(
+        &mut self,
+        start: TExpr,
+        end: TExpr,
+        inclusive: bool,
+    ) -> (usize, usize, Option<u8>) {
+        let (counter, end_slot) = (self.alloc_slot(1), self.alloc_slot(1));
+        self.compile_expression(start);
+        self.current_bytecode.extend_from_slice(&[0x36, counter]); // istore
+        self.compile_expression(end);
+        self.current_bytecode.extend_from_slice(&[0x36, end_slot]);
+        let start_pos = self.current_bytecode.len();
+        self.current_bytecode
+            .extend_from_slice(&[0x15, counter, 0x15, end_slot]);
+        let jump_pos = self.current_bytecode.len();
+        let exit_op = if inclusive { 0xA3 } else { 0xA2 }; // if_icmpgt / if_icmpge
+        self.current_bytecode
+            .extend_from_slice(&[exit_op, 0x00, 0x00]);
+        self.current_bytecode.extend_from_slice(&[0x15, counter]);
+        (start_pos, jump_pos, Some(counter))
+    }
+
+    // arr[a..b] -> Arrays.copyOfRange(arr, a, b + 1); s[a..<b] -> s.substring(a, b).
+    // Antes se validan los límites para fallar con un mensaje en términos de Kujav.
+    pub fn compile_slice(&mut self, container: TExpr, name: &str, range: TExpr) {
+        let TExprKind::Range(start, end, inclusive) = range.kind else {
+            unreachable!("el slice siempre contiene un rango");
+        };
+        let container_t = container.ktype.clone();
+        let (from, to) = (self.alloc_slot(1), self.alloc_slot(1));
+        self.compile_expression(*start);
+        self.current_bytecode.extend_from_slice(&[0x36, from]);
+        self.compile_expression(*end);
+        if inclusive {
+            self.current_bytecode.extend_from_slice(&[0x04, 0x60]); // iconst_1; iadd
+        }
+        self.current_bytecode.extend_from_slice(&[0x36, to]);
+
+        self.current_bytecode
+            .extend_from_slice(&[0x15, from, 0x15, to]);
+        self.compile_expression(container.clone());
+        self.emit_length(&container_t);
+        let u_idx = self.cp.add_utf8(name);
+        let s_idx = self.cp.add_string(u_idx);
+        self.emit_ldc(s_idx);
+        let class_name = self.class_name.clone();
+        self.emit_invoke(0xB8, &class_name, CHECK_RANGE, CHECK_RANGE_DESC);
+        self.uses_range_check = true;
+
+        self.compile_expression(container);
+        self.current_bytecode
+            .extend_from_slice(&[0x15, from, 0x15, to]);
+        match &container_t {
+            KType::Array(elem) => {
+                let elem_sig = self.java.type_sig(elem);
+                if is_ref_sig(&elem_sig) {
+                    self.emit_invoke(
+                        0xB8,
+                        "java/util/Arrays",
+                        "copyOfRange",
+                        "([Ljava/lang/Object;II)[Ljava/lang/Object;",
+                    );
+                    if elem_sig != "Ljava/lang/Object;" {
+                        let target = self.java.class_ref(&container_t);
+                        self.emit_checkcast(&target);
+                    }
+                } else {
+                    let desc = format!("([{0}II)[{0}", elem_sig);
+                    self.emit_invoke(0xB8, "java/util/Arrays", "copyOfRange", &desc);
+                }
+            }
+            _ => self.emit_invoke(
+                0xB6,
+                "java/lang/String",
+                "substring",
+                "(II)Ljava/lang/String;",
+            ),
+        }
+    }
+
+    fn emit_length(&mut self, container: &KType) {
+        match container {
+            KType::Array(_) => self.current_bytecode.push(0xBE), // arraylength
+            _ => self.emit_invoke(0xB6, "java/lang/String", "length", "()I"),
+        }
+    }
+
+    // kujav$checkRange(desde, hasta, longitud, nombre): lanza IndexOutOfBoundsException
+    // con "Rango 2..<6 fuera de los límites de 'arr' (longitud 3)"
+    pub fn compile_range_check_helper(&mut self) {
+        let saved = std::mem::take(&mut self.current_bytecode);
+        let mut jumps = Vec::new();
+        // desde < 0 || hasta > longitud || desde > hasta
+        for check in [&[0x1A, 0x9B][..], &[0x1B, 0x1C, 0xA3], &[0x1A, 0x1B, 0xA3]] {
+            self.current_bytecode.extend_from_slice(check);
+            jumps.push(self.current_bytecode.len() - 1);
+            self.current_bytecode.extend_from_slice(&[0x00, 0x00]);
+        }
+        self.current_bytecode.push(0xB1);
+        let throw_pos = self.current_bytecode.len();
+        for op_pos in jumps {
+            let off = (throw_pos - op_pos) as i16;
+            self.current_bytecode[op_pos + 1..op_pos + 3].copy_from_slice(&off.to_be_bytes());
+        }
+
+        let exception = self.class_const("java/lang/IndexOutOfBoundsException");
+        self.current_bytecode.push(0xBB);
+        self.current_bytecode
+            .extend_from_slice(&exception.to_be_bytes());
+        self.current_bytecode.push(0x59);
+        let builder = self.class_const("java/lang/StringBuilder");
+        self.current_bytecode.push(0xBB);
+        self.current_bytecode
+            .extend_from_slice(&builder.to_be_bytes());
+        self.current_bytecode.push(0x59);
+        self.emit_invoke(0xB7, "java/lang/StringBuilder", "<init>", "()V");
+        // Texto seguido del argumento (iload/aload) que se añade detrás
+        let parts: [(&str, Option<(u8, &str)>); 5] = [
+            ("Rango ", Some((0x1A, "I"))),
+            ("..<", Some((0x1B, "I"))),
+            (
+                " fuera de los límites de '",
+                Some((0x2D, "Ljava/lang/String;")),
+            ),
+            ("' (longitud ", Some((0x1C, "I"))),
+            (")", None),
+        ];
+        for (text, arg) in parts {
+            self.emit_append("Ljava/lang/String;", Some(text));
+            if let Some((load, sig)) = arg {
+                self.current_bytecode.push(load);
+                self.emit_append(sig, None);
+            }
+        }
+        self.emit_invoke(
+            0xB6,
+            "java/lang/StringBuilder",
+            "toString",
+            "()Ljava/lang/String;",
+        );
+        self.emit_invoke(
+            0xB7,
+            "java/lang/IndexOutOfBoundsException",
+            "<init>",
+            "(Ljava/lang/String;)V",
+        );
+        self.current_bytecode.push(0xBF); // athrow
+
+        let bytecode = std::mem::replace(&mut self.current_bytecode, saved);
+        self.methods.push(MethodInfo {
+            access_flags: 0x100A, // private static synthetic
+            name_idx: self.cp.add_utf8(CHECK_RANGE),
+            sig_idx: self.cp.add_utf8(CHECK_RANGE_DESC),
+            max_stack: self.max_stack(&bytecode),
+            bytecode,
+            max_locals: 4,
+            attributes: Vec::new(),
+        });
+    }
+
+    // StringBuilder.append del valor en la pila, o del texto dado
+    fn emit_append(&mut self, sig: &str, text: Option<&str>) {
+        if let Some(text) = text {
+            let u_idx = self.cp.add_utf8(text);
+            let s_idx = self.cp.add_string(u_idx);
+            self.emit_ldc(s_idx);
+        }
+        let desc = format!("({})Ljava/lang/StringBuilder;", sig);
+        self.emit_invoke(0xB6, "java/lang/StringBuilder", "append", &desc);
+    }
+}
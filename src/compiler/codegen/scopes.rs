@@ -0,0 +1,117 @@
+// src/compiler/codegen/scopes.rs
+//
+// Ámbitos de bloque: cada cuerpo de `if`/`while`/`for` y de función abre uno. Al
+// cerrarlo sus locales dejan de existir y sus slots se reutilizan.
+use crate::compiler::codegen::{Compiler, slot_size};
+use crate::compiler::ir::{TStmt, VarId};
+use crate::compiler::types::KType;
+
+pub struct CodeScope {
+    first_slot: u8,
+    declared: Vec<VarId>,
+}
+
+impl Compiler {
+    pub fn push_scope(&mut self) {
+        self.scopes.push(CodeScope {
+            first_slot: self.next_slot,
+            declared: Vec::new(),
+        });
+    }
+
+    pub fn pop_scope(&mut self) {
+        let scope = self.scopes.pop().expect("pop_scope sin push_scope");
+        for id in scope.declared {
+            self.locals.remove(&id);
+        }
+        self.next_slot = scope.first_slot;
+    }
+
+    pub fn compile_block(&mut self, body: Vec<TStmt>) {
+        self.push_scope();
+        for s in body {
+            self.compile_statement(s);
+        }
+        self.pop_scope();
+    }
+
+    // Slots para variables y temporales; max_slot da el max_locals del método
+    pub fn alloc_slot(&mut self, size: u8) -> u8 {
+        let slot = self.next_slot;
+        self.next_slot += size;
+        self.max_slot = self.max_slot.max(self.next_slot);
+        slot
+    }
+
+    // Slot de la local; la primera vez se reserva en el bloque actual
+    pub fn declare_local(&mut self, id: VarId, ktype: &KType) -> u8 {
+        if let Some(&slot) = self.locals.get(&id) {
+            return slot;
+        }
+        let slot = self.alloc_slot(slot_size(&self.java.type_sig(ktype)));
+        self.locals.insert(id, slot);
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.declared.push(id);
+        }
+        slot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::java_symbols::JavaSymbols;
+    use crate::compiler::semantics::SemanticAnalyzer;
+    use crate::parser;
+    use crate::reader::class_index::ClassIndex;
+    use std::collections::HashMap;
+
+    #[test]
+    fn sibling_blocks_reuse_slots_without_touching_live_locals() {
+        let mut kujav = Compiler::new("T", JavaSymbols::new(ClassIndex::new()), HashMap::new());
+        let outer = kujav.declare_local(0, &KType::Int);
+        kujav.push_scope();
+        let first = kujav.declare_local(1, &KType::Long);
+        kujav.pop_scope();
+        kujav.push_scope();
+        let second = kujav.declare_local(2, &KType::Int);
+        let third = kujav.declare_local(3, &KType::Int);
+        kujav.pop_scope();
+        let after = kujav.declare_local(4, &KType::String);
+
+        assert_eq!(outer, 1); // El slot 0 es el String[] de main
+        assert_eq!((first, second, third), (2, 2, 3));
+        // Declarada tras los bloques, ocupa el primer slot que dejaron libre
+        assert_eq!(after, 2);
+        assert_eq!(kujav.locals[&0], 1);
+        assert!(!kujav.locals.contains_key(&1) && !kujav.locals.contains_key(&3));
+        assert_eq!(kujav.max_slot, 4);
+    }
+
+    #[test]
+    fn function_max_locals_counts_only_the_live_blocks() {
+        let source = "\
+function f(c: Bool): Int
+    local a = 1
+    if c then
+        local b = 2
+        print b
+    else
+        local d: Long = 3
+        print d
+    end
+    local e = 4
+    return a + e
+end
+print f(true)
+";
+        let mut analyzer = SemanticAnalyzer::new(ClassIndex::new());
+        let program = analyzer.analyze(&parser::parse_to_ast(source)).unwrap();
+        let mut kujav = Compiler::new("T", analyzer.java, analyzer.functions);
+        for stmt in program {
+            kujav.compile_statement(stmt);
+        }
+        // c, a y el Long del else (b y e reutilizan su primer slot)
+        assert_eq!(kujav.methods[0].max_locals, 4);
+    }
+}
@@ -0,0 +1,257 @@
+// src/compiler/codegen/stack.rs
+//
+// max_stack de cada método: se recorre el bytecode generado siguiendo los saltos y
+// se suma el efecto de cada instrucción sobre la pila, contado en slots.
+use crate::compiler::codegen::{Compiler, arg_slots, slot_size};
+
+impl Compiler {
+    pub fn max_stack(&self, code: &[u8]) -> u16 {
+        let mut depths: Vec<Option<u16>> = vec![None; code.len()];
+        let mut pending = vec![(0usize, 0u16)];
+        let mut max = 0;
+        while let Some((pc, depth)) = pending.pop() {
+            if pc >= code.len() || depths[pc].is_some() {
+                continue;
+            }
+            depths[pc] = Some(depth);
+            let (len, pops, pushes) = self.stack_effect(code, pc);
+            let after = depth
+                .checked_sub(pops)
+                .unwrap_or_else(|| panic!("error interno del compilador: pila vacía en {}", pc))
+                + pushes;
+            max = max.max(depth).max(after);
+
+            let opcode = code[pc];
+            let offsets = match opcode {
+                0x99..=0xA7 | 0xC6 | 0xC7 => {
+                    vec![i16::from_be_bytes([code[pc + 1], code[pc + 2]]) as i32]
+                }
+                0xC8 => vec![read_i32(code, pc + 1)], // goto_w
+                0xAA | 0xAB => switch_layout(code, pc).1,
+                _ => Vec::new(),
+            };
+            for offset in offsets {
+                pending.push(((pc as isize + offset as isize) as usize, after));
+            }
+            // goto, los switch, xreturn y athrow no continúan en la instrucción siguiente
+            if !matches!(opcode, 0xA7 | 0xAA | 0xAB | 0xAC..=0xB1 | 0xBF | 0xC8) {
+                pending.push((pc + len, after));
+            }
+        }
+        max
+    }
+
+    // (Longitud de la instrucción, slots que consume, slots que deja)
+    fn stack_effect(&self, code: &[u8], pc: usize) -> (usize, u16, u16) {
+        let opcode = code[pc];
+        let index = || u16::from_be_bytes([code[pc + 1], code[pc + 2]]);
+        let descriptor = || {
+            self.cp
+                .member_descriptor(index())
+                .expect("referencia a miembro en el ConstantPool")
+        };
+        match opcode {
+            0x00 => (1, 0, 0),
+            0x01..=0x08 | 0x0B..=0x0D => (1, 0, 1),
+            0x09 | 0x0A | 0x0E | 0x0F => (1, 0, 2),
+            0x10 => (2, 0, 1),
+            0x11 => (3, 0, 1),
+            0x12 => (2, 0, 1),
+            0x13 => (3, 0, 1),
+            0x14 => (3, 0, 2), // ldc2_w
+            0x15 | 0x17 | 0x19 => (2, 0, 1),
+            0x16 | 0x18 => (2, 0, 2),
+            0x1A..=0x2D => {
+                let wide = matches!((opcode - 0x1A) / 4, 1 | 3); // lload_n y dload_n
+                (1, 0, if wide { 2 } else { 1 })
+            }
+            0x2E | 0x30 | 0x32..=0x35 => (1, 2, 1),
+            0x2F | 0x31 => (1, 2, 2),
+            0x36 | 0x38 | 0x3A => (2, 1, 0),
+            0x37 | 0x39 => (2, 2, 0),
+            0x3B..=0x4E => {
+                let wide = matches!((opcode - 0x3B) / 4, 1 | 3); // lstore_n y dstore_n
+                (1, if wide { 2 } else { 1 }, 0)
+            }
+            0x4F | 0x51 | 0x53..=0x56 => (1, 3, 0),
+            0x50 | 0x52 => (1, 4, 0),
+            0x57 => (1, 1, 0),
+            0x58 => (1, 2, 0),
+            0x59 => (1, 1, 2),
+            0x5A => (1, 2, 3),
+            0x5B => (1, 3, 4),
+            0x5C => (1, 2, 4),
+            0x5D => (1, 3, 5),
+            0x5E => (1, 4, 6),
+            0x5F => (1, 2, 2),
+            // Aritmética: int, long, float y double se alternan
+            0x60..=0x73 => match (opcode - 0x60) % 4 {
+                1 | 3 => (1, 4, 2),
+                _ => (1, 2, 1),
+            },
+            0x74 | 0x76 => (1, 1, 1),
+            0x75 | 0x77 => (1, 2, 2),
+            0x78 | 0x7A | 0x7C => (1, 2, 1),
+            0x79 | 0x7B | 0x7D => (1, 3, 2), // El desplazamiento de un long es un int
+            0x7E | 0x80 | 0x82 => (1, 2, 1),
+            0x7F | 0x81 | 0x83 => (1, 4, 2),
+            0x84 => (3, 0, 0),
+            0x85 | 0x87 | 0x8C | 0x8D => (1, 1, 2),
+            0x86 | 0x8B | 0x91..=0x93 => (1, 1, 1),
+            0x88 | 0x89 | 0x8E | 0x90 => (1, 2, 1),
+            0x8A | 0x8F => (1, 2, 2),
+            0x94 | 0x97 | 0x98 => (1, 4, 1),
+            0x95 | 0x96 => (1, 2, 1),
+            0x99..=0x9E | 0xC6 | 0xC7 => (3, 1, 0),
+            0x9F..=0xA6 => (3, 2, 0),
+            0xA7 => (3, 0, 0),
+            0xAA | 0xAB => (switch_layout(code, pc).0, 1, 0),
+            0xAC | 0xAE | 0xB0 => (1, 1, 0),
+            0xAD | 0xAF => (1, 2, 0),
+            0xB1 => (1, 0, 0),
+            0xB2..=0xB5 => {
+                let size = slot_size(descriptor()) as u16;
+                match opcode {
+                    0xB2 => (3, 0, size), // getstatic
+                    0xB3 => (3, size, 0), // putstatic
+                    0xB4 => (3, 1, size), // getfield
+                    _ => (3, 1 + size, 0),
+                }
+            }
+            0xB6..=0xB9 => {
+                let desc = descriptor();
+                let ret = &desc[desc.find(')').expect("descriptor de método") + 1..];
+                let receiver = (opcode != 0xB8) as u16;
+                let pushes = if ret == "V" { 0 } else { slot_size(ret) as u16 };
+                let len = if opcode == 0xB9 { 5 } else { 3 };
+                (len, receiver + arg_slots(desc) as u16, pushes)
+            }
+            0xBB => (3, 0, 1),
+            0xBC => (2, 1, 1),
+            0xBD | 0xC0 | 0xC1 => (3, 1, 1),
+            0xBE => (1, 1, 1),
+            0xBF | 0xC2 | 0xC3 => (1, 1, 0),
+            // wide: el mismo efecto que la instrucción que amplía, con un índice de 16 bits
+            0xC4 => match code[pc + 1] {
+                0x84 => (6, 0, 0),
+                op @ (0x15..=0x19 | 0x36..=0x3A) => {
+                    let (_, pops, pushes) = self.stack_effect(&[op, 0], 0);
+                    (4, pops, pushes)
+                }
+                op => panic!(
+                    "error interno del compilador: wide sobre el opcode 0x{:02X}",
+                    op
+                ),
+            },
+            0xC5 => (4, code[pc + 3] as u16, 1), // multianewarray: una dimensión por valor
+            0xC8 => (5, 0, 0),
+            _ => panic!(
+                "error interno del compilador: opcode 0x{:02X} sin efecto de pila conocido",
+                opcode
+            ),
+        }
+    }
+}
+
+fn read_i32(code: &[u8], at: usize) -> i32 {
+    i32::from_be_bytes([code[at], code[at + 1], code[at + 2], code[at + 3]])
+}
+
+// (Longitud, desplazamientos de los saltos) de un tableswitch o lookupswitch: tras el
+// opcode hay relleno hasta múltiplo de 4, el salto por defecto y la tabla de casos
+fn switch_layout(code: &[u8], pc: usize) -> (usize, Vec<i32>) {
+    let start = (pc + 4) & !3;
+    let mut offsets = vec![read_i32(code, start)];
+    let cases = if code[pc] == 0xAA {
+        let (low, high) = (read_i32(code, start + 4), read_i32(code, start + 8));
+        let count = (high - low + 1) as usize;
+        offsets.extend((0..count).map(|i| read_i32(code, start + 12 + 4 * i)));
+        start + 12 + 4 * count
+    } else {
+        let count = read_i32(code, start + 4) as usize;
+        offsets.extend((0..count).map(|i| read_i32(code, start + 12 + 8 * i)));
+        start + 8 + 8 * count
+    };
+    (cases - pc, offsets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::assembler;
+    use crate::compiler::java_symbols::JavaSymbols;
+    use crate::reader::class_index::ClassIndex;
+    use std::collections::HashMap;
+
+    fn compiler() -> Compiler {
+        Compiler::new("T", JavaSymbols::new(ClassIndex::new()), HashMap::new())
+    }
+
+    #[test]
+    fn straight_line_code_keeps_the_deepest_point() {
+        // iconst_1 iconst_2 iadd pop return
+        assert_eq!(compiler().max_stack(&[0x04, 0x05, 0x60, 0x57, 0xB1]), 2);
+    }
+
+    #[test]
+    fn wide_loads_and_iinc_use_the_long_forms() {
+        let code = [
+            0xC4, 0x16, 0x01, 0x2C, // wide lload 300
+            0xC4, 0x84, 0x01, 0x2D, 0xFF, 0xFF, // wide iinc 301 -1
+            0x58, // pop2
+            0xB1,
+        ];
+        assert_eq!(compiler().max_stack(&code), 2);
+    }
+
+    #[test]
+    fn tableswitch_follows_every_case() {
+        let mut code = vec![0x03, 0xAA, 0x00, 0x00]; // iconst_0, tableswitch y relleno
+        for word in [23i32, 0, 1, 24, 23] {
+            // default, low, high y los casos 0 y 1, relativos al tableswitch
+            code.extend_from_slice(&word.to_be_bytes());
+        }
+        code.push(0xB1); // 24: return
+        code.extend_from_slice(&[0x09, 0x09, 0x58, 0x58, 0xB1]); // 25: dos lconst_0
+        assert_eq!(compiler().max_stack(&code), 4);
+    }
+
+    #[test]
+    fn lookupswitch_follows_every_pair() {
+        let mut code = vec![0x03, 0xAB, 0x00, 0x00]; // iconst_0, lookupswitch y relleno
+        for word in [19i32, 1, 7, 20] {
+            // default, número de pares y el par (7, salto)
+            code.extend_from_slice(&word.to_be_bytes());
+        }
+        code.push(0xB1); // 20: return
+        code.extend_from_slice(&[0x0E, 0x58, 0xB1]); // 21: dconst_0 pop2 return
+        assert_eq!(compiler().max_stack(&code), 2);
+    }
+
+    #[test]
+    fn invokes_use_the_descriptor_slots() {
+        let mut kujav = compiler();
+        kujav.current_bytecode.extend_from_slice(&[0x09, 0x01]); // lconst_0 aconst_null
+        kujav.emit_invoke(0xB8, "p/A", "f", "(JLjava/lang/String;)D");
+        kujav.current_bytecode.extend_from_slice(&[0x01, 0x03]); // aconst_null iconst_0
+        kujav.emit_invoke_interface("p/I", "g", "(I)J");
+        // El double de f sigue debajo: 2 + receptor + int
+        kujav
+            .current_bytecode
+            .extend_from_slice(&[0x58, 0x58, 0xB1]);
+        let code = std::mem::take(&mut kujav.current_bytecode);
+        assert_eq!(kujav.max_stack(&code), 4);
+    }
+
+    #[test]
+    fn asm_only_method_is_measured_from_its_bytes() {
+        let mut kujav = compiler();
+        let body = "getstatic java/lang/System.out Ljava/io/PrintStream;\n".to_string()
+            + &"lconst_1\n".repeat(12)
+            + &"pop2\n".repeat(11)
+            + "invokevirtual java/io/PrintStream.println(J)V\nreturn\n";
+        let items = assembler::parse(&body).unwrap();
+        let code = assembler::assemble(&items, &mut kujav.cp, |_| 0);
+        assert_eq!(kujav.max_stack(&code), 25);
+    }
+}
@@ -1,144 +1,229 @@
 // src/compiler/codegen/statements.rs
-use crate::compiler::codegen::{Compiler, MethodInfo};
-use crate::parser::ast::Stmt;
+use crate::compiler::assembler;
+use crate::compiler::calls::jvm_name;
+use crate::compiler::codegen::{
+    Compiler, MethodInfo, access_flags, is_ref_sig, load_op, return_op, slot_size, store_op,
+};
+use crate::compiler::ir::{Place, TExprKind, TStmt};
+use crate::compiler::types::KType;
 
 impl Compiler {
-    pub fn compile_statement(&mut self, stmt: Stmt) {
+    pub fn compile_statement(&mut self, stmt: TStmt) {
         match stmt {
-            Stmt::Let(name, expr, _) => {
-                let is_ref = self.is_ref_expr(&expr);
-                let slot = self.get_or_create_slot(&name);
-
-                let type_sig = if is_ref {
-                    "Ljava/lang/Object;".to_string()
-                } else {
-                    "I".to_string()
-                };
-                self.variable_types.insert(name.clone(), type_sig);
-
-                self.compile_expression(expr);
-                self.current_bytecode.push(if is_ref { 0x3A } else { 0x36 });
-                self.current_bytecode.push(slot);
+            TStmt::Const(name, value, annotations, visibility) => {
+                self.compile_const(name, value, annotations, visibility)
+            }
+            TStmt::Global(name, ktype, annotations, visibility) => {
+                self.declare_global(&name, &ktype, &annotations, visibility)
             }
-            Stmt::Print(expr) => {
-                let is_ref = self.is_ref_expr(&expr);
+            // Las globales se inicializan en <clinit>, en el orden de declaración
+            TStmt::StaticInit(init) => {
+                let main = self.enter_clinit();
+                self.compile_statement(*init);
+                self.leave_clinit(main);
+            }
+            TStmt::Let(place, value) => {
+                self.compile_expression(value);
+                self.emit_store(place);
+            }
+            TStmt::Print(expr) => {
+                let sig = self.java.type_sig(&expr.ktype);
+                let is_tuple = matches!(expr.ktype, KType::Tuple(_));
                 // Llamamos a los métodos auxiliares definidos abajo
                 self.prepare_println_call();
                 self.compile_expression(expr);
-                self.emit_println_invoke(is_ref);
+                if is_tuple {
+                    self.emit_tuple_to_string();
+                }
+                self.emit_println_invoke(&sig);
             }
-            Stmt::If(cond, if_b, else_b) => {
+            TStmt::LetMulti(places, expr) => self.compile_destructuring(places, expr),
+            TStmt::If(cond, if_b, else_b, then_returns) => {
                 self.compile_expression(cond);
                 let opcode_pos = self.current_bytecode.len();
                 self.current_bytecode.push(0x99);
                 let jump_to_else_idx = self.current_bytecode.len();
                 self.current_bytecode.extend_from_slice(&[0x00, 0x00]);
-                for s in if_b {
-                    self.compile_statement(s);
-                }
+                self.compile_block(if_b);
                 if let Some(else_stmts) = else_b {
+                    // Si el then ya retorna no hay goto: su destino podría quedar fuera del código
                     let goto_pos = self.current_bytecode.len();
-                    self.current_bytecode.push(0xA7);
-                    let jump_to_end_idx = self.current_bytecode.len();
-                    self.current_bytecode.extend_from_slice(&[0x00, 0x00]);
+                    if !then_returns {
+                        self.current_bytecode.extend_from_slice(&[0xA7, 0x00, 0x00]);
+                    }
                     let off_else = (self.current_bytecode.len() - opcode_pos) as i16;
                     self.current_bytecode[jump_to_else_idx..jump_to_else_idx + 2]
                         .copy_from_slice(&off_else.to_be_bytes());
-                    for s in else_stmts {
-                        self.compile_statement(s);
+                    self.compile_block(else_stmts);
+                    if !then_returns {
+                        let off_end = (self.current_bytecode.len() - goto_pos) as i16;
+                        self.current_bytecode[goto_pos + 1..goto_pos + 3]
+                            .copy_from_slice(&off_end.to_be_bytes());
                     }
-                    let off_end = (self.current_bytecode.len() - goto_pos) as i16;
-                    self.current_bytecode[jump_to_end_idx..jump_to_end_idx + 2]
-                        .copy_from_slice(&off_end.to_be_bytes());
                 } else {
                     let off = (self.current_bytecode.len() - opcode_pos) as i16;
                     self.current_bytecode[jump_to_else_idx..jump_to_else_idx + 2]
                         .copy_from_slice(&off.to_be_bytes());
                 }
             }
-            Stmt::While(cond, body) => {
+            TStmt::While(cond, body) => {
                 let start_pos = self.current_bytecode.len();
-                self.compile_expression(cond);
-                let ifeq_pos = self.current_bytecode.len();
-                self.current_bytecode.push(0x99);
-                let jump_to_end_idx = self.current_bytecode.len();
-                self.current_bytecode.extend_from_slice(&[0x00, 0x00]);
-                for s in body {
-                    self.compile_statement(s);
-                }
+                // `while true` no comprueba la condición: sin salida, el ifeq apuntaría al final del código
+                let endless = matches!(cond.kind, TExprKind::Boolean(true));
+                let ifeq_pos = if endless {
+                    None
+                } else {
+                    self.compile_expression(cond);
+                    let pos = self.current_bytecode.len();
+                    self.current_bytecode.extend_from_slice(&[0x99, 0x00, 0x00]);
+                    Some(pos)
+                };
+                self.compile_block(body);
                 let goto_pos = self.current_bytecode.len();
                 self.current_bytecode.push(0xA7);
                 let off_start = (start_pos as i32 - goto_pos as i32) as i16;
                 self.current_bytecode
                     .extend_from_slice(&off_start.to_be_bytes());
-                let off_end = (self.current_bytecode.len() - ifeq_pos) as i16;
-                self.current_bytecode[jump_to_end_idx..jump_to_end_idx + 2]
-                    .copy_from_slice(&off_end.to_be_bytes());
+                if let Some(ifeq_pos) = ifeq_pos {
+                    let off_end = (self.current_bytecode.len() - ifeq_pos) as i16;
+                    self.current_bytecode[ifeq_pos + 1..ifeq_pos + 3]
+                        .copy_from_slice(&off_end.to_be_bytes());
+                }
             }
-            Stmt::Function(name, params, body, return_type) => {
+            TStmt::For(var, iterable, body) => self.compile_for(var, iterable, body),
+            TStmt::Function(name, params, body, return_type, annotations, visibility) => {
+                let param_annotations: Vec<&[_]> =
+                    params.iter().map(|(_, _, a)| a.as_slice()).collect();
+                let attributes =
+                    self.annotation_attributes(&annotations, "METHOD", &param_annotations);
                 let mut p_sigs = String::new();
-                let (old_bc, old_vars, old_types, old_slot) = (
+                let (old_bc, old_locals, old_scopes) = (
                     std::mem::take(&mut self.current_bytecode),
-                    std::mem::take(&mut self.variables),
-                    std::mem::take(&mut self.variable_types),
-                    self.next_slot,
+                    std::mem::take(&mut self.locals),
+                    std::mem::take(&mut self.scopes),
                 );
-
-                self.next_slot = 0;
-                for (p_name, p_type) in params {
-                    p_sigs.push_str(&p_type.to_jvm_sig());
-                    self.variables.insert(p_name.clone(), self.next_slot);
-                    self.variable_types.insert(p_name, p_type.to_jvm_sig());
-                    self.next_slot += 1;
+                let (old_slot, old_max) = (self.next_slot, self.max_slot);
+                (self.next_slot, self.max_slot) = (0, 0);
+                // Los parámetros tienen su propio ámbito, exterior al del cuerpo
+                self.push_scope();
+                for (id, p_type, _) in params {
+                    p_sigs.push_str(&self.java.type_sig(&p_type));
+                    self.declare_local(id, &p_type);
                 }
+                let desc = format!("({}){}", p_sigs, self.java.type_sig(&return_type));
 
-                for s in body {
-                    self.compile_statement(s);
-                }
+                self.compile_block(body);
+                self.pop_scope();
 
-                if return_type == crate::compiler::types::KType::Void {
+                if return_type == KType::Void {
                     self.current_bytecode.push(0xB1);
                 }
 
+                let variadic = self.functions.get(&name).is_some_and(|sig| sig.variadic);
+                let bytecode = std::mem::take(&mut self.current_bytecode);
                 self.methods.push(MethodInfo {
-                    name_idx: self.cp.add_utf8(&name),
-                    sig_idx: self
-                        .cp
-                        .add_utf8(&format!("({}){}", p_sigs, return_type.to_jvm_sig())),
-                    bytecode: std::mem::take(&mut self.current_bytecode),
-                    max_locals: self.next_slot as u16,
+                    access_flags: access_flags(visibility) | if variadic { 0x0080 } else { 0 }, // ACC_VARARGS
+                    name_idx: self.cp.add_utf8(jvm_name(&name)),
+                    sig_idx: self.cp.add_utf8(&desc),
+                    max_stack: self.max_stack(&bytecode),
+                    bytecode,
+                    max_locals: self.max_slot as u16,
+                    attributes,
                 });
+                self.compile_default_bridges(&name, &desc, access_flags(visibility));
 
                 self.current_bytecode = old_bc;
-                self.variables = old_vars;
-                self.variable_types = old_types;
-                self.next_slot = old_slot;
+                self.locals = old_locals;
+                self.scopes = old_scopes;
+                (self.next_slot, self.max_slot) = (old_slot, old_max);
             }
-            Stmt::Call(name, args) => {
-                use crate::parser::ast::Expr;
-                self.compile_expression(Expr::Call(name, args));
-                self.current_bytecode.push(0x57);
+            TStmt::Expr(expr) => {
+                let sig = self.java.type_sig(&expr.ktype);
+                self.compile_expression(expr);
+                match sig.as_str() {
+                    "V" => {}
+                    "J" | "D" => self.current_bytecode.push(0x58), // pop2
+                    _ => self.current_bytecode.push(0x57),
+                }
             }
-            Stmt::Return(maybe_expr) => {
+            TStmt::Return(maybe_expr) => {
                 if let Some(expr) = maybe_expr {
-                    let is_ref = self.is_ref_expr(&expr);
+                    let sig = self.java.type_sig(&expr.ktype);
                     self.compile_expression(expr);
-                    self.current_bytecode.push(if is_ref { 0xB0 } else { 0xAC });
+                    self.current_bytecode.push(return_op(&sig));
                 } else {
                     self.current_bytecode.push(0xB1);
                 }
             }
-            Stmt::IndexAssign(name, idx_expr, val_expr) => {
-                if let Some(&slot) = self.variables.get(&name) {
-                    self.current_bytecode.push(0x19); // aload
-                    self.current_bytecode.push(slot);
-                    self.compile_expression(idx_expr);
-                    self.compile_expression(val_expr);
-                    self.current_bytecode.push(0x4F); // iastore
-                }
+            TStmt::Assert(cond, message) => self.compile_assert(cond, message),
+            TStmt::Asm(items, visible) => {
+                let locals = &self.locals;
+                let code = assembler::assemble(&items, &mut self.cp, |name| locals[&visible[name]]);
+                self.current_bytecode.extend(code);
             }
+            TStmt::IndexAssign(container, idx, val) => self.compile_index_set(container, idx, val),
         }
     }
+
+    // Guarda el valor de la cima de la pila: putstatic en una global; una local
+    // recibe su slot en el bloque actual la primera vez que se guarda
+    pub fn emit_store(&mut self, place: Place) {
+        match place {
+            Place::Global(name, ktype) => {
+                let sig = self.java.type_sig(&ktype);
+                self.emit_field_access(0xB3, &name, &sig); // putstatic
+            }
+            Place::Local(id, ktype) => {
+                let slot = self.declare_local(id, &ktype);
+                let sig = self.java.type_sig(&ktype);
+                self.current_bytecode
+                    .extend_from_slice(&[store_op(&sig), slot]);
+            }
+        }
+    }
+
+    // Una sobrecarga por cada número de argumentos omitibles, para que Java pueda
+    // llamar a f(x) en lugar de f(x, 64, "mob"): carga sus parámetros, apila los
+    // valores por defecto restantes y delega en la firma completa
+    fn compile_default_bridges(&mut self, name: &str, full_desc: &str, flags: u16) {
+        let Some(sig) = self.functions.get(name).cloned() else {
+            return;
+        };
+        let class_name = self.class_name.clone();
+        let ret_sig = self.java.type_sig(&sig.ret);
+        for count in sig.required()..sig.params.len() {
+            let mut p_sigs = String::new();
+            let mut slot = 0u8;
+            for (_, p_type, _) in &sig.params[..count] {
+                let p_sig = self.java.type_sig(p_type);
+                self.current_bytecode
+                    .extend_from_slice(&[load_op(&p_sig), slot]);
+                slot += slot_size(&p_sig);
+                p_sigs.push_str(&p_sig);
+            }
+            for (_, _, default) in &sig.params[count..] {
+                self.emit_const_value(default.as_ref().expect("parámetros por defecto al final"));
+            }
+            self.emit_invoke(0xB8, &class_name, jvm_name(name), full_desc);
+            self.current_bytecode.push(match ret_sig.as_str() {
+                "V" => 0xB1,
+                s => return_op(s),
+            });
+
+            let desc = format!("({}){}", p_sigs, ret_sig);
+            let bytecode = std::mem::take(&mut self.current_bytecode);
+            self.methods.push(MethodInfo {
+                access_flags: flags,
+                name_idx: self.cp.add_utf8(jvm_name(name)),
+                sig_idx: self.cp.add_utf8(&desc),
+                max_stack: self.max_stack(&bytecode),
+                bytecode,
+                max_locals: slot as u16,
+                attributes: Vec::new(),
+            });
+        }
+    }
+
     pub fn prepare_println_call(&mut self) {
         let sys_u = self.cp.add_utf8("java/lang/System");
         let sys_c = self.cp.add_class(sys_u);
@@ -151,11 +236,15 @@ impl Compiler {
             .extend_from_slice(&f_out.to_be_bytes());
     }
 
-    pub fn emit_println_invoke(&mut self, is_ref: bool) {
-        let sig_str = if is_ref {
-            "(Ljava/lang/Object;)V"
-        } else {
-            "(I)V"
+    pub fn emit_println_invoke(&mut self, sig: &str) {
+        let sig_str = match sig {
+            "Z" => "(Z)V",
+            "C" => "(C)V",
+            "J" => "(J)V",
+            "F" => "(F)V",
+            "D" => "(D)V",
+            s if is_ref_sig(s) => "(Ljava/lang/Object;)V",
+            _ => "(I)V",
         };
         let ps_u = self.cp.add_utf8("java/io/PrintStream");
         let ps_c = self.cp.add_class(ps_u);
@@ -166,12 +255,4 @@ impl Compiler {
         self.current_bytecode.push(0xB6); // invokevirtual
         self.current_bytecode.extend_from_slice(&m_pr.to_be_bytes());
     }
-
-    fn get_or_create_slot(&mut self, name: &str) -> u8 {
-        *self.variables.entry(name.to_string()).or_insert_with(|| {
-            let s = self.next_slot;
-            self.next_slot += 1;
-            s
-        })
-    }
 }
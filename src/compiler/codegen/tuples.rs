@@ -0,0 +1,55 @@
+// src/compiler/codegen/tuples.rs
+//
+// Las tuplas se representan como Object[] con los primitivos envueltos.
+use crate::compiler::codegen::{Compiler, is_ref_sig};
+use crate::compiler::ir::{Place, TExpr};
+use crate::compiler::types::KType;
+
+impl Compiler {
+    pub fn compile_tuple(&mut self, elems: Vec<TExpr>) {
+        self.emit_int(elems.len() as i32);
+        let object_idx = self.class_const("java/lang/Object");
+        self.current_bytecode.push(0xBD); // anewarray
+        self.current_bytecode
+            .extend_from_slice(&object_idx.to_be_bytes());
+        for (i, e) in elems.into_iter().enumerate() {
+            self.current_bytecode.push(0x59); // dup
+            self.emit_int(i as i32);
+            self.compile_boxed(e);
+            self.current_bytecode.push(0x53); // aastore
+        }
+    }
+
+    // `local a, b = expr`: se reparte el Object[] sin variable temporal (dup por valor)
+    pub fn compile_destructuring(&mut self, places: Vec<Place>, expr: TExpr) {
+        let KType::Tuple(elems) = expr.ktype.clone() else {
+            unreachable!("destructuring validado en el análisis semántico");
+        };
+        self.compile_expression(expr);
+        let last = places.len() - 1;
+        for (i, (place, t)) in places.into_iter().zip(elems).enumerate() {
+            if i < last {
+                self.current_bytecode.push(0x59);
+            }
+            self.emit_int(i as i32);
+            self.current_bytecode.push(0x32); // aaload
+            self.emit_cast("Ljava/lang/Object;", &t, false);
+            // Asignar a una local exterior puede ensanchar el elemento (Int -> Long)
+            let sig = self.java.type_sig(&t);
+            if sig != self.java.type_sig(place.ktype()) && !is_ref_sig(&sig) {
+                self.emit_cast(&sig, place.ktype(), false);
+            }
+            self.emit_store(place);
+        }
+    }
+
+    // print (1, "a") muestra [1, a] en lugar de [Ljava.lang.Object;@...
+    pub fn emit_tuple_to_string(&mut self) {
+        self.emit_invoke(
+            0xB8,
+            "java/util/Arrays",
+            "toString",
+            "([Ljava/lang/Object;)Ljava/lang/String;",
+        );
+    }
+}
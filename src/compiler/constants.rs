@@ -0,0 +1,79 @@
+// src/compiler/constants.rs
+use crate::compiler::types::KType;
+use crate::parser::ast::Expr;
+use std::collections::HashMap;
+
+// Valor de un `const`, escrito como ConstantValue e insertado en cada uso
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstValue {
+    Int(i32),
+    Bool(bool),
+    String(String),
+}
+
+impl ConstValue {
+    pub fn ktype(&self) -> KType {
+        match self {
+            ConstValue::Int(_) => KType::Int,
+            ConstValue::Bool(_) => KType::Bool,
+            ConstValue::String(_) => KType::String,
+        }
+    }
+
+    // Literal equivalente, para insertar el valor donde se usa (p. ej. un argumento por defecto)
+    pub fn to_expr(&self) -> Expr {
+        match self {
+            ConstValue::Int(v) => Expr::Number(*v),
+            ConstValue::Bool(v) => Expr::Boolean(*v),
+            ConstValue::String(v) => Expr::String(v.clone()),
+        }
+    }
+
+    fn concat_text(&self) -> String {
+        match self {
+            ConstValue::Int(v) => v.to_string(),
+            ConstValue::Bool(v) => v.to_string(),
+            ConstValue::String(v) => v.clone(),
+        }
+    }
+}
+
+// Plegado en tiempo de compilación: literales, otras constantes y operadores sobre ellas
+pub fn eval_const(expr: &Expr, consts: &HashMap<String, ConstValue>) -> Result<ConstValue, String> {
+    match expr {
+        Expr::Number(v) => Ok(ConstValue::Int(*v)),
+        Expr::Boolean(v) => Ok(ConstValue::Bool(*v)),
+        Expr::String(v) => Ok(ConstValue::String(v.clone())),
+        Expr::Identifier(n, _) => consts
+            .get(n)
+            .cloned()
+            .ok_or(format!("'{}' no es una constante", n)),
+        Expr::Binary(l, op, r) => {
+            let (lv, rv) = (eval_const(l, consts)?, eval_const(r, consts)?);
+            match (lv, op.as_str(), rv) {
+                (ConstValue::Int(a), "+", ConstValue::Int(b)) => {
+                    Ok(ConstValue::Int(a.wrapping_add(b)))
+                }
+                (ConstValue::Int(a), "-", ConstValue::Int(b)) => {
+                    Ok(ConstValue::Int(a.wrapping_sub(b)))
+                }
+                (ConstValue::Int(a), "*", ConstValue::Int(b)) => {
+                    Ok(ConstValue::Int(a.wrapping_mul(b)))
+                }
+                (ConstValue::Int(_), "/", ConstValue::Int(0)) => {
+                    Err("división por cero en una constante".into())
+                }
+                (ConstValue::Int(a), "/", ConstValue::Int(b)) => {
+                    Ok(ConstValue::Int(a.wrapping_div(b)))
+                }
+                (a @ ConstValue::String(_), "+", b) | (a, "+", b @ ConstValue::String(_)) => {
+                    Ok(ConstValue::String(a.concat_text() + &b.concat_text()))
+                }
+                (a, "==", b) => Ok(ConstValue::Bool(a == b)),
+                (a, "!=", b) => Ok(ConstValue::Bool(a != b)),
+                _ => Err(format!("operador '{}' no admitido en una constante", op)),
+            }
+        }
+        _ => Err("el valor no es una expresión constante".into()),
+    }
+}
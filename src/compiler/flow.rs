@@ -0,0 +1,371 @@
+// src/compiler/flow.rs
+// Análisis de flujo sobre el AST: qué bloques nunca llegan a su final
+use crate::compiler::assembler;
+use crate::parser::ast::{Arg, Expr, Span, Stmt};
+use std::collections::{HashMap, HashSet};
+
+// Todo camino del bloque termina en un return (o no termina nunca): lo que siga es
+// inalcanzable. Sin `break`, un `while true` solo se abandona con return
+pub fn always_returns(body: &[Stmt]) -> bool {
+    body.iter().any(|stmt| match stmt {
+        Stmt::Return(_) => true,
+        Stmt::If(_, then_body, Some(else_body)) => {
+            always_returns(then_body) && always_returns(else_body)
+        }
+        Stmt::While(Expr::Boolean(true), _) => true,
+        Stmt::Asm(body) => {
+            assembler::parse(body).is_ok_and(|items| !assembler::reaches_end(&items))
+        }
+        _ => false,
+    })
+}
+
+// Asignación definida: cada `local` se inicializa al declararse, así que una local
+// está asignada desde su declaración hasta el final de su bloque. Cualquier uso
+// anterior, o posterior a su bloque, se rechaza con la posición del uso y la de la
+// declaración. Los nombres que el código no declara los resuelve el análisis semántico
+pub fn check_assignments(items: &[Stmt]) -> Result<(), String> {
+    let mut check = AssignmentCheck {
+        globals: HashSet::new(),
+        module_decls: HashMap::new(),
+        frame_decls: HashMap::new(),
+        initializing: Vec::new(),
+    };
+    for stmt in items {
+        if let Stmt::Let(name, .., span) = stmt {
+            check.module_decls.entry(name.clone()).or_insert(*span);
+        }
+    }
+    check.frame_decls = declarations(items);
+    check.walk(items, &mut HashSet::new(), true)
+}
+
+struct AssignmentCheck {
+    globals: HashSet<String>, // Constantes y globales del módulo ya declaradas
+    module_decls: HashMap<String, Span>, // Globales del módulo, para explicar los usos adelantados
+    frame_decls: HashMap<String, Span>, // Locales que declara el cuerpo que se recorre
+    initializing: Vec<String>, // Nombres del `local` cuyo valor se está comprobando
+}
+
+// Primera declaración de cada local del cuerpo, también en bloques anidados (no en funciones)
+fn declarations(body: &[Stmt]) -> HashMap<String, Span> {
+    let mut decls = HashMap::new();
+    collect_declarations(body, &mut decls);
+    decls
+}
+
+fn collect_declarations(body: &[Stmt], decls: &mut HashMap<String, Span>) {
+    for stmt in body {
+        match stmt {
+            Stmt::Let(name, .., span) => {
+                decls.entry(name.clone()).or_insert(*span);
+            }
+            Stmt::LetMulti(names, _, span) => {
+                for name in names {
+                    decls.entry(name.clone()).or_insert(*span);
+                }
+            }
+            Stmt::For(var, _, body, span) => {
+                decls.entry(var.clone()).or_insert(*span);
+                collect_declarations(body, decls);
+            }
+            Stmt::If(_, then_body, else_body) => {
+                collect_declarations(then_body, decls);
+                if let Some(else_body) = else_body {
+                    collect_declarations(else_body, decls);
+                }
+            }
+            Stmt::While(_, body) => collect_declarations(body, decls),
+            _ => {}
+        }
+    }
+}
+
+impl AssignmentCheck {
+    // `assigned`: locales asignadas en este punto; los bloques trabajan sobre una copia
+    fn walk(
+        &mut self,
+        body: &[Stmt],
+        assigned: &mut HashSet<String>,
+        module: bool,
+    ) -> Result<(), String> {
+        for stmt in body {
+            match stmt {
+                Stmt::Let(name, expr, ..) => {
+                    self.check_initializer(std::slice::from_ref(name), expr, assigned)?;
+                    assigned.insert(name.clone());
+                    if module {
+                        self.globals.insert(name.clone());
+                    }
+                }
+                Stmt::LetMulti(names, expr, _) => {
+                    self.check_initializer(names, expr, assigned)?;
+                    assigned.extend(names.iter().cloned());
+                    if module {
+                        self.globals.extend(names.iter().cloned());
+                    }
+                }
+                Stmt::Const(name, ..) => {
+                    self.globals.insert(name.clone());
+                }
+                Stmt::Print(expr) | Stmt::Return(Some(expr)) => self.check_expr(expr, assigned)?,
+                Stmt::Call(name, args) => self.check_call(name, args, assigned)?,
+                Stmt::Assert(cond, message) => {
+                    self.check_expr(cond, assigned)?;
+                    if let Some(message) = message {
+                        self.check_expr(message, assigned)?;
+                    }
+                }
+                Stmt::IndexAssign(name, idx, value) => {
+                    self.check_name(name, None, assigned)?;
+                    self.check_expr(idx, assigned)?;
+                    self.check_expr(value, assigned)?;
+                }
+                Stmt::If(cond, then_body, else_body) => {
+                    self.check_expr(cond, assigned)?;
+                    self.walk(then_body, &mut assigned.clone(), false)?;
+                    if let Some(else_body) = else_body {
+                        self.walk(else_body, &mut assigned.clone(), false)?;
+                    }
+                }
+                Stmt::While(cond, body) => {
+                    self.check_expr(cond, assigned)?;
+                    self.walk(body, &mut assigned.clone(), false)?;
+                }
+                Stmt::For(var, iterable, body, _) => {
+                    self.check_expr(iterable, assigned)?;
+                    let mut inner = assigned.clone();
+                    inner.insert(var.clone());
+                    self.walk(body, &mut inner, false)?;
+                }
+                Stmt::Function(_, params, body, ..) => {
+                    // Una función solo ve las globales ya declaradas y sus parámetros
+                    let mut inner: HashSet<String> = self.globals.clone();
+                    inner.extend(params.iter().map(|(p, ..)| p.clone()));
+                    let outer_decls = std::mem::replace(&mut self.frame_decls, declarations(body));
+                    let result = self.walk(body, &mut inner, false);
+                    self.frame_decls = outer_decls;
+                    result?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn check_initializer(
+        &mut self,
+        names: &[String],
+        expr: &Expr,
+        assigned: &HashSet<String>,
+    ) -> Result<(), String> {
+        self.initializing = names.to_vec();
+        let result = self.check_expr(expr, assigned);
+        self.initializing.clear();
+        result
+    }
+
+    fn check_expr(&self, expr: &Expr, assigned: &HashSet<String>) -> Result<(), String> {
+        match expr {
+            Expr::Identifier(name, span) => self.check_name(name, Some(*span), assigned),
+            Expr::Binary(l, _, r) | Expr::Range(l, r, _) => {
+                self.check_expr(l, assigned)?;
+                self.check_expr(r, assigned)
+            }
+            Expr::Call(name, args) => self.check_call(name, args, assigned),
+            Expr::ArrayLiteral(elems) | Expr::Tuple(elems) | Expr::NewArray(_, elems) => {
+                elems.iter().try_for_each(|e| self.check_expr(e, assigned))
+            }
+            Expr::MapLiteral(entries) => entries.iter().try_for_each(|(k, v)| {
+                self.check_expr(k, assigned)?;
+                self.check_expr(v, assigned)
+            }),
+            Expr::ArrayAccess(name, inner) | Expr::Slice(name, inner) => {
+                self.check_name(name, None, assigned)?;
+                self.check_expr(inner, assigned)
+            }
+            Expr::Length(inner) | Expr::Cast(inner, ..) | Expr::TypeTest(inner, _) => {
+                self.check_expr(inner, assigned)
+            }
+            Expr::Number(_) | Expr::String(_) | Expr::Boolean(_) | Expr::Input => Ok(()),
+        }
+    }
+
+    // `valor.método(...)` usa el receptor
+    fn check_call(
+        &self,
+        name: &str,
+        args: &[Arg],
+        assigned: &HashSet<String>,
+    ) -> Result<(), String> {
+        if let Some((receiver, _)) = name.split_once('.') {
+            self.check_name(receiver, None, assigned)?;
+        }
+        args.iter()
+            .try_for_each(|(_, value)| self.check_expr(value, assigned))
+    }
+
+    // Los usos dentro de `nombre[i]` o `nombre.método()` no guardan su posición
+    fn check_name(
+        &self,
+        name: &str,
+        used_at: Option<Span>,
+        assigned: &HashSet<String>,
+    ) -> Result<(), String> {
+        if assigned.contains(name) || self.globals.contains(name) {
+            return Ok(());
+        }
+        let Some(&declared_at) = self
+            .frame_decls
+            .get(name)
+            .or_else(|| self.module_decls.get(name))
+        else {
+            return Ok(());
+        };
+        Err(match used_at {
+            _ if self.initializing.iter().any(|n| n == name) => format!(
+                "'{}' se usa en su propio valor inicial (declarada en {})",
+                name, declared_at
+            ),
+            None => format!(
+                "'{}' no está asignada en este punto (declarada en {})",
+                name, declared_at
+            ),
+            Some(span) if span < declared_at || !self.frame_decls.contains_key(name) => format!(
+                "'{}' se usa en {} antes de su declaración en {}",
+                name, span, declared_at
+            ),
+            Some(span) => format!(
+                "'{}' se usa en {} fuera del bloque donde se declaró ({})",
+                name, span, declared_at
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    // Cuerpo de la única función de `source`
+    fn function_body(source: &str) -> Vec<Stmt> {
+        match parser::parse_to_ast(source).remove(0) {
+            Stmt::Function(_, _, body, ..) => body,
+            other => panic!("se esperaba una función: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn if_without_else_can_fall_off_the_end() {
+        let body = function_body(
+            "function f(c: Bool): Int\n    if c then\n        return 1\n    end\nend\n",
+        );
+        assert!(!always_returns(&body));
+    }
+
+    #[test]
+    fn if_else_returns_only_when_both_branches_do() {
+        let both = function_body(
+            "function f(c: Bool): Int\n    if c then\n        return 1\n    else\n        return 2\n    end\nend\n",
+        );
+        assert!(always_returns(&both));
+        let one = function_body(
+            "function f(c: Bool): Int\n    if c then\n        return 1\n    else\n        print 2\n    end\nend\n",
+        );
+        assert!(!always_returns(&one));
+    }
+
+    #[test]
+    fn only_an_endless_loop_never_reaches_its_end() {
+        let endless =
+            function_body("function f(): Int\n    while true do\n        print 1\n    end\nend\n");
+        assert!(always_returns(&endless));
+        let bounded = function_body(
+            "function f(c: Bool): Int\n    while c do\n        return 1\n    end\nend\n",
+        );
+        assert!(!always_returns(&bounded));
+    }
+
+    #[test]
+    fn local_from_one_branch_is_not_assigned_after_the_if() {
+        let source = "\
+function f(c: Bool): Int
+    if c then
+        local x = 1
+    else
+        print 0
+    end
+    return x
+end
+";
+        let err = check_assignments(&parser::parse_to_ast(source)).unwrap_err();
+        assert_eq!(
+            err,
+            "'x' se usa en 7:12 fuera del bloque donde se declaró (3:15)"
+        );
+    }
+
+    #[test]
+    fn loop_body_locals_end_with_the_loop() {
+        let inside = "\
+function f(n: Int)
+    for i in 1..n do
+        print total
+        local total = i
+    end
+end
+";
+        let err = check_assignments(&parser::parse_to_ast(inside)).unwrap_err();
+        assert_eq!(
+            err,
+            "'total' se usa en 3:15 antes de su declaración en 4:15"
+        );
+
+        let after = "\
+function f(n: Int)
+    while n > 0 do
+        local paso = 1
+    end
+    print paso
+end
+";
+        let err = check_assignments(&parser::parse_to_ast(after)).unwrap_err();
+        assert_eq!(
+            err,
+            "'paso' se usa en 5:11 fuera del bloque donde se declaró (3:15)"
+        );
+    }
+
+    #[test]
+    fn reported_spans_point_at_the_use_and_the_declaration() {
+        let source = "print contador\nlocal contador = 0\n";
+        let err = check_assignments(&parser::parse_to_ast(source)).unwrap_err();
+        assert_eq!(
+            err,
+            "'contador' se usa en 1:7 antes de su declaración en 2:7"
+        );
+
+        let own = "function f()\n    local x = x + 1\nend\n";
+        let err = check_assignments(&parser::parse_to_ast(own)).unwrap_err();
+        assert_eq!(
+            err,
+            "'x' se usa en su propio valor inicial (declarada en 2:11)"
+        );
+    }
+
+    #[test]
+    fn declared_locals_are_assigned_for_the_rest_of_their_block() {
+        let source = "\
+function f(c: Bool): Int
+    local x = 1
+    if c then
+        local y = x
+        return y
+    end
+    return x
+end
+";
+        assert!(check_assignments(&parser::parse_to_ast(source)).is_ok());
+    }
+}
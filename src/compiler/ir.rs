@@ -0,0 +1,102 @@
+// src/compiler/ir.rs
+//
+// Representación intermedia tipada: la produce el análisis semántico y la consume
+// la generación de código. Cada expresión lleva su KType, cada nombre está resuelto
+// (local con su VarId, global o constante sustituida por su valor) y las
+// conversiones implícitas, la sobrecarga elegida y el orden de los argumentos ya
+// están decididos, así que el codegen no vuelve a inferir tipos.
+use crate::compiler::assembler::AsmItem;
+use crate::compiler::constants::ConstValue;
+use crate::compiler::java_symbols::JavaMethod;
+use crate::compiler::types::KType;
+use crate::parser::ast::{Annotation, Visibility};
+use std::collections::HashMap;
+
+pub type VarId = usize; // Cada declaración de una local o parámetro recibe uno nuevo
+
+#[derive(Debug, Clone)]
+pub struct TExpr {
+    pub kind: TExprKind,
+    pub ktype: KType, // Tipo del valor que deja en la pila
+}
+
+#[derive(Debug, Clone)]
+pub enum TExprKind {
+    Number(i32),
+    String(String),
+    Boolean(bool),
+    Const(ConstValue), // Uso de un `const`: se inserta su valor
+    Local(VarId),
+    Global(String),                         // Campo estático de la clase
+    Binary(Box<TExpr>, String, Box<TExpr>), // Los operandos numéricos ya están promocionados al tipo común
+    Call(String, Vec<TExpr>), // (Función Kujav o extensión, Args en el orden de los parámetros)
+    JavaCall(Option<Box<TExpr>>, String, JavaMethod, Vec<TExpr>), // (Receptor si es de instancia, Método, Sobrecarga, Args)
+    Input,
+    ArrayLiteral(Vec<TExpr>), // Arreglo, List o Set según el tipo; también los varargs empaquetados
+    MapLiteral(Vec<(TExpr, TExpr)>),
+    Tuple(Vec<TExpr>),
+    Index(Box<TExpr>, Box<TExpr>), // (Arreglo, lista o mapa, Índice o clave)
+    NewArray(Vec<TExpr>),          // Tamaño de cada dimensión
+    Length(Box<TExpr>),
+    Range(Box<TExpr>, Box<TExpr>, bool), // Solo en un `for` o un slice
+    Slice(Box<TExpr>, String, Box<TExpr>), // (Arreglo o String, Nombre para el mensaje de error, Rango)
+    Cast(Box<TExpr>, bool),                // (Expr, es `as?`)
+    Convert(Box<TExpr>), // Conversión implícita: ensanchamiento, boxing o unboxing
+    TypeTest(Box<TExpr>, KType), // expr is Tipo
+}
+
+// Destino de una asignación, con el tipo con el que se guarda
+#[derive(Debug, Clone)]
+pub enum Place {
+    Local(VarId, KType),
+    Global(String, KType),
+}
+
+#[derive(Debug, Clone)]
+pub enum TStmt {
+    Const(String, ConstValue, Vec<Annotation>, Option<Visibility>),
+    Global(String, KType, Vec<Annotation>, Option<Visibility>), // Declara el campo; le sigue la asignación de su valor
+    StaticInit(Box<TStmt>), // Se compila en <clinit>, en el orden de declaración
+    Let(Place, TExpr),      // Una local se declara la primera vez que se guarda
+    LetMulti(Vec<Place>, TExpr), // (Destinos, Tupla)
+    Print(TExpr),
+    If(TExpr, Vec<TStmt>, Option<Vec<TStmt>>, bool), // (Condición, Then, Else, el then siempre retorna)
+    While(TExpr, Vec<TStmt>),
+    For(VarId, TExpr, Vec<TStmt>), // (Variable, Rango, arreglo o colección, Cuerpo)
+    Function(
+        String,
+        Vec<(VarId, KType, Vec<Annotation>)>,
+        Vec<TStmt>,
+        KType,
+        Vec<Annotation>,
+        Option<Visibility>,
+    ), // (Nombre, Params, Cuerpo, Retorno, Anotaciones, Visibilidad)
+    Expr(TExpr),                   // Llamada cuyo valor se descarta
+    Return(Option<TExpr>),         // El valor ya está convertido al tipo de retorno
+    IndexAssign(TExpr, TExpr, TExpr), // (Contenedor, Índice o clave, Valor)
+    Asm(Vec<AsmItem>, HashMap<String, VarId>), // (Instrucciones, Locales visibles por nombre)
+    Assert(TExpr, Option<TExpr>),
+}
+
+impl TExpr {
+    pub fn new(kind: TExprKind, ktype: KType) -> Self {
+        Self { kind, ktype }
+    }
+
+    // El valor adaptado a `target`; la semántica ya comprobó que la conversión existe
+    pub fn convert_to(self, target: &KType) -> TExpr {
+        if self.ktype == *target {
+            self
+        } else {
+            TExpr::new(TExprKind::Convert(Box::new(self)), target.clone())
+        }
+    }
+}
+
+impl Place {
+    pub fn ktype(&self) -> &KType {
+        match self {
+            Place::Local(_, t) | Place::Global(_, t) => t,
+        }
+    }
+}
@@ -0,0 +1,431 @@
+// src/compiler/java_symbols.rs
+use crate::compiler::types::KType;
+use crate::reader::class_index::{
+    ACC_FINAL, ACC_INTERFACE, ACC_PRIVATE, ACC_PUBLIC, ACC_STATIC, ACC_VARARGS, ClassIndex,
+    ClassInfo, MemberInfo,
+};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+// Método elegido para una llamada `Clase.método(args)` o `valor.método(args)`
+#[derive(Debug, Clone)]
+pub struct JavaMethod {
+    pub owner: String, // Estático: la clase que lo declara; de instancia: la clase del receptor
+    pub interface: bool, // De instancia sobre una interfaz: invokeinterface
+    pub descriptor: String,
+    pub params: Vec<KType>,
+    pub ret: KType,
+    pub packs_varargs: bool, // Los argumentos sobrantes van en el arreglo del último parámetro
+}
+
+// Resolución de nombres de clases Java a partir de los imports del archivo
+pub struct JavaSymbols {
+    pub classes: ClassIndex,
+    imports: HashMap<String, String>, // Nombre simple -> nombre interno
+    wildcards: Vec<String>,           // Paquetes importados con `.*`
+    used_imports: RefCell<HashSet<String>>, // Imports (nombre interno o paquete `.*`) que resolvieron algún nombre
+    package: String, // Paquete interno de la clase compilada ("" si no se declara)
+}
+
+impl JavaSymbols {
+    pub fn new(classes: ClassIndex) -> Self {
+        Self {
+            classes,
+            imports: HashMap::new(),
+            wildcards: Vec::new(),
+            used_imports: RefCell::new(HashSet::new()),
+            package: String::new(),
+        }
+    }
+
+    pub fn set_package(&mut self, name: &str) {
+        self.package = name.replace('.', "/");
+    }
+
+    // Nombre interno de la clase generada: com/org/plugin/Nombre
+    pub fn qualified_class(&self, class_name: &str) -> String {
+        if self.package.is_empty() {
+            class_name.to_string()
+        } else {
+            format!("{}/{}", self.package, class_name)
+        }
+    }
+
+    // Público, o sin `private` y declarado en el mismo paquete que la clase generada
+    // (`protected` exige además heredar de la clase, y la clase generada hereda de Object)
+    fn accessible(&self, class: &ClassInfo, member: &MemberInfo) -> bool {
+        let same_package = class.name.rsplit_once('/').map_or("", |(pkg, _)| pkg) == self.package;
+        let class_ok = class.access_flags & ACC_PUBLIC != 0 || same_package;
+        let member_ok = member.access_flags & ACC_PUBLIC != 0
+            || (member.access_flags & ACC_PRIVATE == 0 && same_package);
+        class_ok && member_ok
+    }
+
+    pub fn add_import(&mut self, path: &str) -> Result<(), String> {
+        let internal = path.replace('.', "/");
+        if let Some(package) = internal.strip_suffix("/*") {
+            if !self.classes.is_empty() && !self.classes.has_package(package) {
+                return Err(format!("Paquete importado '{}' no encontrado", path));
+            }
+            self.wildcards.push(package.to_string());
+            return Ok(());
+        }
+        if !self.classes.is_empty() && self.classes.get(&internal).is_none() {
+            return Err(format!(
+                "Import '{}' no encontrado en el classpath ni en la JDK",
+                path
+            ));
+        }
+        let simple = internal.rsplit('/').next().unwrap_or(&internal).to_string();
+        self.imports.insert(simple, internal);
+        Ok(())
+    }
+
+    // Orden: imports explícitos, nombre completo, imports `.*` y por último java.lang
+    pub fn resolve(&self, name: &str) -> Option<&ClassInfo> {
+        if let Some(internal) = self.imports.get(name) {
+            self.used_imports.borrow_mut().insert(internal.clone());
+            return self.classes.get(internal);
+        }
+        self.classes
+            .get(name)
+            .or_else(|| {
+                self.wildcards.iter().find_map(|pkg| {
+                    let class = self.classes.get(&format!("{pkg}/{name}"))?;
+                    self.used_imports.borrow_mut().insert(pkg.clone());
+                    Some(class)
+                })
+            })
+            .or_else(|| self.classes.get(&format!("java/lang/{name}")))
+    }
+
+    // Imports que no resolvieron ningún nombre, tal como se escribieron
+    pub fn unused_imports(&self) -> Vec<String> {
+        let used = self.used_imports.borrow();
+        let mut unused: Vec<String> = self
+            .imports
+            .values()
+            .filter(|internal| !used.contains(*internal))
+            .map(|internal| internal.replace('/', "."))
+            .chain(
+                self.wildcards
+                    .iter()
+                    .filter(|pkg| !used.contains(*pkg))
+                    .map(|pkg| format!("{}.*", pkg.replace('/', "."))),
+            )
+            .collect();
+        unused.sort();
+        unused
+    }
+
+    pub fn internal_name(&self, name: &str) -> String {
+        match self.resolve(name) {
+            Some(class) => class.name.clone(),
+            None => self
+                .imports
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| name.replace('.', "/")),
+        }
+    }
+
+    pub fn type_sig(&self, t: &KType) -> String {
+        match t {
+            KType::Array(inner) => format!("[{}", self.type_sig(inner)),
+            KType::Custom(name) => format!("L{};", self.internal_name(name)),
+            _ => t.to_jvm_sig(),
+        }
+    }
+
+    // Nombre interno usado por checkcast/instanceof (los arreglos usan su descriptor)
+    pub fn class_ref(&self, t: &KType) -> String {
+        match t {
+            KType::Array(_) | KType::Tuple(_) => self.type_sig(t),
+            KType::String => "java/lang/String".into(),
+            KType::Custom(name) => self.internal_name(name),
+            KType::List(_) | KType::Set(_) | KType::Map(_, _) => t
+                .collection_classes()
+                .map(|(iface, _)| iface)
+                .unwrap_or_default()
+                .into(),
+            other => other.box_class().unwrap_or("java/lang/Object").into(),
+        }
+    }
+
+    // Recorre superclases e interfaces; `None` si alguna clase falta en el índice
+    pub fn is_subclass(&self, from: &str, to: &str) -> Option<bool> {
+        if from == to || to == "java/lang/Object" {
+            return Some(true);
+        }
+        let class = self.classes.get(from)?;
+        let mut unknown = false;
+        for parent in class.super_name.iter().chain(class.interfaces.iter()) {
+            match self.is_subclass(parent, to) {
+                Some(true) => return Some(true),
+                Some(false) => {}
+                None => unknown = true,
+            }
+        }
+        if unknown { None } else { Some(false) }
+    }
+
+    // Regla de javac para casts entre referencias: basta con que exista algún valor
+    // de `from` que también sea `to`. Sin información en el índice se permite.
+    pub fn cast_possible(&self, from: &KType, to: &KType) -> bool {
+        const ARRAY_SUPERS: [&str; 3] = [
+            "java/lang/Object",
+            "java/lang/Cloneable",
+            "java/io/Serializable",
+        ];
+        match (from, to) {
+            (KType::Array(a), KType::Array(b)) => {
+                if a.is_reference() && b.is_reference() {
+                    self.cast_possible(a, b)
+                } else {
+                    a == b
+                }
+            }
+            (KType::Array(_), other) | (other, KType::Array(_)) => {
+                ARRAY_SUPERS.contains(&self.class_ref(other).as_str())
+            }
+            _ => {
+                let (a, b) = (self.class_ref(from), self.class_ref(to));
+                if self.is_subclass(&a, &b) != Some(false)
+                    || self.is_subclass(&b, &a) != Some(false)
+                {
+                    return true;
+                }
+                let (Some(ca), Some(cb)) = (self.classes.get(&a), self.classes.get(&b)) else {
+                    return true;
+                };
+                let is_interface = |c: &ClassInfo| c.access_flags & ACC_INTERFACE != 0;
+                let is_final = |c: &ClassInfo| c.access_flags & ACC_FINAL != 0;
+                match (is_interface(ca), is_interface(cb)) {
+                    (true, true) => true,
+                    (true, false) => !is_final(cb),
+                    (false, true) => !is_final(ca),
+                    (false, false) => false,
+                }
+            }
+        }
+    }
+
+    // Coste de pasar `arg` a un parámetro `param` como en una invocación Java:
+    // 0 idéntico, 1 ensanchamiento primitivo, 2 subtipo, 3 boxing/unboxing.
+    // `None` si no es aplicable; con clases ausentes del índice se permite.
+    pub fn arg_cost(&self, param: &KType, arg: &KType) -> Option<u8> {
+        if self.type_sig(param) == self.type_sig(arg) {
+            return Some(0);
+        }
+        match (param, arg) {
+            (KType::Array(p), KType::Array(a)) if p.is_reference() && a.is_reference() => {
+                self.arg_cost(p, a).map(|_| 2)
+            }
+            (KType::Array(_), _) | (_, KType::Array(_) | KType::Tuple(_)) => {
+                (self.class_ref(param) == "java/lang/Object").then_some(2)
+            }
+            _ => match (param.is_reference(), arg.is_reference()) {
+                (false, false) => widens(arg, param).then_some(1),
+                (true, true) => (self.is_subclass(&self.class_ref(arg), &self.class_ref(param))
+                    != Some(false))
+                .then_some(2),
+                (true, false) => {
+                    let boxed = arg.box_class()?;
+                    (self.is_subclass(boxed, &self.class_ref(param)) != Some(false)).then_some(3)
+                }
+                (false, true) => {
+                    (Some(self.class_ref(arg).as_str()) == param.box_class()).then_some(3)
+                }
+            },
+        }
+    }
+
+    // Como javac: primero sin empaquetar varargs y, si nada encaja, empaquetando.
+    // Entre los aplicables gana el de menor coste total (el más específico).
+    pub fn resolve_static(
+        &self,
+        class: &str,
+        method: &str,
+        args: &[KType],
+    ) -> Result<JavaMethod, String> {
+        let info = self.resolve(class).ok_or(format!(
+            "Clase '{}' no encontrada en el classpath ni en la JDK",
+            class
+        ))?;
+        let mut candidates = Vec::new();
+        let mut current = Some(info);
+        while let Some(c) = current {
+            for m in &c.methods {
+                if m.name == method && m.access_flags & ACC_STATIC != 0 {
+                    candidates.push((c, m));
+                }
+            }
+            current = c.super_name.as_deref().and_then(|s| self.classes.get(s));
+        }
+        if candidates.is_empty() {
+            return Err(format!(
+                "'{}' no tiene un método estático '{}'",
+                class, method
+            ));
+        }
+        let (c, m, params, ret, packs_varargs) =
+            self.choose_overload(class, method, candidates, args)?;
+        if c.access_flags & ACC_INTERFACE != 0 {
+            return Err(format!(
+                "'{}.{}' es un método estático de interfaz y requiere class files de Java 8 (Kujav genera la versión 49)",
+                class, method
+            ));
+        }
+        Ok(JavaMethod {
+            owner: c.name.clone(),
+            interface: false,
+            descriptor: m.descriptor.clone(),
+            params,
+            ret,
+            packs_varargs,
+        })
+    }
+
+    // Métodos de instancia del tipo del receptor, sus superclases e interfaces.
+    // `None` si no declaran ninguno con ese nombre (la llamada puede ser una extensión).
+    pub fn resolve_instance(
+        &self,
+        receiver: &KType,
+        method: &str,
+        args: &[KType],
+    ) -> Result<Option<JavaMethod>, String> {
+        if !receiver.is_reference() || matches!(receiver, KType::Array(_) | KType::Tuple(_)) {
+            return Ok(None);
+        }
+        let owner = self.class_ref(receiver);
+        let Some(info) = self.classes.get(&owner) else {
+            return Ok(None);
+        };
+        let mut candidates: Vec<(&ClassInfo, &MemberInfo)> = Vec::new();
+        let mut pending = vec![info];
+        let mut visited = Vec::new();
+        while let Some(c) = pending.pop() {
+            if visited.contains(&c.name) {
+                continue;
+            }
+            visited.push(c.name.clone());
+            for m in &c.methods {
+                // Una redefinición oculta al método heredado con el mismo descriptor
+                if m.name == method
+                    && m.access_flags & ACC_STATIC == 0
+                    && !candidates.iter().any(|(_, o)| o.descriptor == m.descriptor)
+                {
+                    candidates.push((c, m));
+                }
+            }
+            let parents = c.interfaces.iter().rev().chain(c.super_name.iter());
+            pending.extend(parents.filter_map(|p| self.classes.get(p)));
+        }
+        // Las interfaces no heredan de Object en el class file, pero sus valores sí
+        if info.access_flags & ACC_INTERFACE != 0
+            && let Some(object) = self.classes.get("java/lang/Object")
+        {
+            for m in object
+                .methods
+                .iter()
+                .filter(|m| m.name == method && m.access_flags & ACC_PUBLIC != 0)
+            {
+                if !candidates.iter().any(|(_, o)| o.descriptor == m.descriptor) {
+                    candidates.push((object, m));
+                }
+            }
+        }
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+        let label = receiver.to_string();
+        let (_, m, params, ret, packs_varargs) =
+            self.choose_overload(&label, method, candidates, args)?;
+        Ok(Some(JavaMethod {
+            interface: info.access_flags & ACC_INTERFACE != 0,
+            owner,
+            descriptor: m.descriptor.clone(),
+            params,
+            ret,
+            packs_varargs,
+        }))
+    }
+
+    fn choose_overload<'a>(
+        &self,
+        class: &str,
+        method: &str,
+        mut candidates: Vec<(&'a ClassInfo, &'a MemberInfo)>,
+        args: &[KType],
+    ) -> Result<(&'a ClassInfo, &'a MemberInfo, Vec<KType>, KType, bool), String> {
+        candidates.retain(|(c, m)| self.accessible(c, m));
+        if candidates.is_empty() {
+            return Err(format!(
+                "'{}.{}' no es accesible desde el paquete de este módulo",
+                class, method
+            ));
+        }
+
+        for packs_varargs in [false, true] {
+            let best = candidates
+                .iter()
+                .filter(|(_, m)| !packs_varargs || m.access_flags & ACC_VARARGS != 0)
+                .filter_map(|(c, m)| {
+                    let (params, ret) = KType::from_method_sig(&m.descriptor);
+                    let cost = self.call_cost(&params, args, packs_varargs)?;
+                    Some((cost, *c, *m, params, ret))
+                })
+                .min_by_key(|(cost, ..)| *cost);
+            if let Some((_, c, m, params, ret)) = best {
+                return Ok((c, m, params, ret, packs_varargs));
+            }
+        }
+        let names: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        Err(format!(
+            "Ningún método '{}.{}' acepta los argumentos ({})",
+            class,
+            method,
+            names.join(", ")
+        ))
+    }
+
+    fn call_cost(&self, params: &[KType], args: &[KType], packs_varargs: bool) -> Option<u32> {
+        let fixed = if packs_varargs {
+            params.len().checked_sub(1)?
+        } else {
+            params.len()
+        };
+        if args.len() < fixed || (!packs_varargs && args.len() != fixed) {
+            return None;
+        }
+        let elem = match params.last() {
+            Some(KType::Array(elem)) if packs_varargs => Some(elem.as_ref()),
+            _ => None,
+        };
+        let mut total = 0;
+        for (i, arg) in args.iter().enumerate() {
+            let param = if i < fixed { &params[i] } else { elem? };
+            total += self.arg_cost(param, arg)? as u32;
+        }
+        Some(total)
+    }
+}
+
+// Ensanchamiento primitivo de Java (byte -> short -> int -> long -> float -> double, char -> int)
+fn widens(from: &KType, to: &KType) -> bool {
+    let rank = |t: &KType| match t {
+        KType::Byte => Some(1),
+        KType::Short | KType::Char => Some(2),
+        KType::Int => Some(3),
+        KType::Long => Some(4),
+        KType::Float => Some(5),
+        KType::Double => Some(6),
+        _ => None,
+    };
+    match (rank(from), rank(to)) {
+        (Some(f), Some(t)) => {
+            f < t && *to != KType::Char && !(*from == KType::Char && *to == KType::Short)
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,213 @@
+// src/compiler/lints.rs
+//
+// Avisos del compilador. Cada lint tiene un nombre estable y un nivel (allow, warn,
+// deny) que se fija en [lints] de kujav.toml; `@allow(lint, ...)` lo silencia en una
+// función o una declaración concreta.
+use crate::errors::{KujavError, KujavResult, KujavWarning};
+use crate::parser::ast::{Annotation, AnnotationValue};
+use std::collections::{BTreeMap, HashMap};
+
+pub const ALLOW: &str = "allow"; // Atributo del compilador: no llega al class file
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    UnusedVariables,
+    UnusedFunctions,
+    UnusedImports,
+    Shadowing,
+    UnreachableCode,
+    ConstantConditions,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 6] = [
+        Lint::UnusedVariables,
+        Lint::UnusedFunctions,
+        Lint::UnusedImports,
+        Lint::Shadowing,
+        Lint::UnreachableCode,
+        Lint::ConstantConditions,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Lint::UnusedVariables => "unused_variables",
+            Lint::UnusedFunctions => "unused_functions",
+            Lint::UnusedImports => "unused_imports",
+            Lint::Shadowing => "shadowing",
+            Lint::UnreachableCode => "unreachable_code",
+            Lint::ConstantConditions => "constant_conditions",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Lint> {
+        Lint::ALL.into_iter().find(|l| l.name() == name)
+    }
+}
+
+// Lints que silencia un `@allow(...)`; el resto de anotaciones se ignoran aquí
+pub fn allowed_lints(annotations: &[Annotation]) -> Result<Vec<Lint>, String> {
+    let mut lints = Vec::new();
+    for ann in annotations.iter().filter(|a| a.name == ALLOW) {
+        if ann.args.is_empty() {
+            return Err("@allow necesita al menos un lint: @allow(unused_variables)".into());
+        }
+        for (key, value) in &ann.args {
+            let name = match value {
+                AnnotationValue::Name(name) if key == "value" => name.as_str(),
+                _ => "",
+            };
+            lints.push(Lint::from_name(name).ok_or_else(|| {
+                let known: Vec<&str> = Lint::ALL.iter().map(|l| l.name()).collect();
+                format!(
+                    "Lint desconocido '{}' en @allow (lints: {})",
+                    name,
+                    known.join(", ")
+                )
+            })?);
+        }
+    }
+    Ok(lints)
+}
+
+// Nivel de cada lint: `warn` salvo que [lints] diga otra cosa
+pub struct LintLevels {
+    levels: HashMap<Lint, LintLevel>,
+}
+
+impl LintLevels {
+    pub fn from_config(config: &BTreeMap<String, String>) -> KujavResult<Self> {
+        let mut levels = HashMap::new();
+        for (name, level) in config {
+            let lint = Lint::from_name(name)
+                .ok_or_else(|| KujavError::toml(format!("unknown lint '{name}' in [lints]")))?;
+            let level = match level.as_str() {
+                "allow" => LintLevel::Allow,
+                "warn" => LintLevel::Warn,
+                "deny" => LintLevel::Deny,
+                other => {
+                    return Err(KujavError::toml(format!(
+                        "[lints] {name} must be \"allow\", \"warn\" or \"deny\", found: {other}"
+                    )));
+                }
+            };
+            levels.insert(lint, level);
+        }
+        Ok(Self { levels })
+    }
+
+    pub fn level(&self, lint: Lint) -> LintLevel {
+        self.levels.get(&lint).copied().unwrap_or(LintLevel::Warn)
+    }
+
+    // Muestra los avisos en `warn`; los de un lint en `deny` detienen la compilación
+    pub fn report(&self, warnings: &[KujavWarning]) -> KujavResult<()> {
+        let mut denied = Vec::new();
+        for w in warnings {
+            let lint = Lint::from_name(w.lint).expect("aviso emitido con un lint conocido");
+            match self.level(lint) {
+                LintLevel::Allow => {}
+                LintLevel::Warn => eprintln!("{w}"),
+                LintLevel::Deny => denied.push(format!("{} [{} = \"deny\"]", w.message, w.lint)),
+            }
+        }
+        if denied.is_empty() {
+            Ok(())
+        } else {
+            Err(KujavError::semantic(denied.join("\n")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::semantics::SemanticAnalyzer;
+    use crate::parser;
+    use crate::reader::class_index::ClassIndex;
+
+    // Lints de los avisos que deja el análisis de `source`
+    fn warned(source: &str) -> Vec<&'static str> {
+        let mut analyzer = SemanticAnalyzer::new(ClassIndex::new());
+        analyzer.analyze(&parser::parse_to_ast(source)).unwrap();
+        analyzer.warnings.iter().map(|w| w.lint).collect()
+    }
+
+    fn levels(entries: &[(&str, &str)]) -> KujavResult<LintLevels> {
+        let config = entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        LintLevels::from_config(&config)
+    }
+
+    fn warning(lint: Lint) -> KujavWarning {
+        KujavWarning {
+            lint: lint.name(),
+            message: "aviso".into(),
+        }
+    }
+
+    #[test]
+    fn levels_default_to_warn_and_follow_the_config() {
+        let levels = levels(&[("shadowing", "allow"), ("unused_imports", "deny")]).unwrap();
+        assert_eq!(levels.level(Lint::Shadowing), LintLevel::Allow);
+        assert_eq!(levels.level(Lint::UnusedImports), LintLevel::Deny);
+        assert_eq!(levels.level(Lint::UnusedVariables), LintLevel::Warn);
+    }
+
+    #[test]
+    fn unknown_lints_and_levels_are_rejected() {
+        let err = levels(&[("nada", "warn")]).err().unwrap().to_string();
+        assert!(err.contains("unknown lint 'nada'"), "{}", err);
+        let err = levels(&[("shadowing", "error")]).err().unwrap().to_string();
+        assert!(err.contains("found: error"), "{}", err);
+    }
+
+    #[test]
+    fn only_denied_lints_stop_the_build() {
+        let levels = levels(&[("shadowing", "allow"), ("unused_imports", "deny")]).unwrap();
+        assert!(levels.report(&[warning(Lint::Shadowing)]).is_ok());
+        assert!(levels.report(&[warning(Lint::UnusedVariables)]).is_ok());
+        let err = levels
+            .report(&[warning(Lint::UnusedImports)])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("aviso [unused_imports = \"deny\"]"), "{}", err);
+    }
+
+    #[test]
+    fn allow_on_a_function_silences_its_locals() {
+        let body = "function f()\n    local x = 1\nend\nf()\n";
+        assert_eq!(warned(body), ["unused_variables"]);
+        assert!(warned(&format!("@allow(unused_variables)\n{body}")).is_empty());
+    }
+
+    #[test]
+    fn allow_on_a_local_only_silences_that_local() {
+        let source = "\
+function f()
+    @allow(unused_variables)
+    local x = 1
+    local y = 2
+end
+f()
+";
+        assert_eq!(warned(source), ["unused_variables"]);
+    }
+
+    #[test]
+    fn allow_names_must_be_known_lints() {
+        let source = "@allow(nada)\nfunction f()\nend\nf()\n";
+        let mut analyzer = SemanticAnalyzer::new(ClassIndex::new());
+        let err = analyzer.analyze(&parser::parse_to_ast(source)).unwrap_err();
+        assert!(err.contains("Lint desconocido 'nada'"), "{}", err);
+    }
+}
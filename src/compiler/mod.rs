@@ -1,4 +1,13 @@
+pub mod annotations;
+pub mod assembler;
+pub mod calls;
+pub mod cfg;
 pub mod codegen;
+pub mod constants;
+pub mod flow;
+pub mod ir;
+pub mod java_symbols;
+pub mod lints;
 pub mod pipeline;
 pub mod semantics;
 pub mod types;
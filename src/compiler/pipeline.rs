@@ -1,27 +1,51 @@
 use std::fs;
 use std::io::Write;
+use std::path::Path;
 
 use zip::write::FileOptions;
 
-use crate::compiler::codegen::{Compiler, MethodInfo};
+use crate::compiler::cfg::CfgContext;
+use crate::compiler::codegen::{Compiler, FieldInfo, MethodInfo};
+use crate::compiler::lints::LintLevels;
 use crate::compiler::semantics::SemanticAnalyzer;
 use crate::errors::{KujavError, KujavResult};
 use crate::parser;
+use crate::reader::class_index::ClassIndex;
 use crate::toml_config::KujavToml;
 
-pub fn check_only(source: &str) -> KujavResult<()> {
-    let ast = parser::parse_to_ast(source);
-    let mut analyzer = SemanticAnalyzer::new();
+pub fn check_only(
+    source: &str,
+    classes: ClassIndex,
+    cfg: &CfgContext,
+    lints: &LintLevels,
+) -> KujavResult<()> {
+    let ast = cfg
+        .apply(parser::parse_to_ast(source))
+        .map_err(KujavError::semantic)?;
+    let mut analyzer = SemanticAnalyzer::new(classes);
     analyzer.analyze(&ast).map_err(KujavError::semantic)?;
-    Ok(())
+    lints.report(&analyzer.warnings)
 }
 
-pub fn compile_to_class(class_name: &str, source: &str, out_path: &str) -> KujavResult<()> {
-    let ast = parser::parse_to_ast(source);
-    let mut analyzer = SemanticAnalyzer::new();
-    analyzer.analyze(&ast).map_err(KujavError::semantic)?;
-
-    let mut kujav = Compiler::new();
+// Escribe `<target_dir>/<paquete>/<clase>.class` y devuelve el nombre interno de la clase
+pub fn compile_to_class(
+    class_name: &str,
+    source: &str,
+    target_dir: &str,
+    classes: ClassIndex,
+    cfg: &CfgContext,
+    lints: &LintLevels,
+) -> KujavResult<String> {
+    let ast = cfg
+        .apply(parser::parse_to_ast(source))
+        .map_err(KujavError::semantic)?;
+    let mut analyzer = SemanticAnalyzer::new(classes);
+    let program = analyzer.analyze(&ast).map_err(KujavError::semantic)?;
+    lints.report(&analyzer.warnings)?;
+
+    let class_name = analyzer.java.qualified_class(class_name);
+    let class_name = class_name.as_str();
+    let mut kujav = Compiler::new(class_name, analyzer.java, analyzer.functions);
     let cls_u = kujav.cp.add_utf8(class_name);
     let this_c = kujav.cp.add_class(cls_u);
     let obj_super_u = kujav.cp.add_utf8("java/lang/Object");
@@ -31,18 +55,50 @@ pub fn compile_to_class(class_name: &str, source: &str, out_path: &str) -> Kujav
     let m_t = kujav.cp.add_utf8("([Ljava/lang/String;)V");
     let c_a = kujav.cp.add_utf8("Code");
 
-    for stmt in ast {
+    for stmt in program {
         kujav.compile_statement(stmt);
     }
+    kujav.compile_entry_call();
     kujav.current_bytecode.push(0xB1);
+    if kujav.uses_range_check {
+        kujav.compile_range_check_helper();
+    }
+    if kujav.uses_assertions {
+        kujav.compile_assertions_field();
+    }
 
-    let mut file = fs::File::create(out_path)?;
+    // Inicializadores de las globales, en el orden en que se declararon
+    if !kujav.clinit_bytecode.is_empty() {
+        let mut bytecode = std::mem::take(&mut kujav.clinit_bytecode);
+        bytecode.push(0xB1);
+        let clinit = MethodInfo {
+            access_flags: 0x0008,
+            name_idx: kujav.cp.add_utf8("<clinit>"),
+            sig_idx: kujav.cp.add_utf8("()V"),
+            max_stack: kujav.max_stack(&bytecode),
+            bytecode,
+            max_locals: kujav.clinit_locals as u16,
+            attributes: Vec::new(),
+        };
+        kujav.methods.push(clinit);
+    }
+
+    let out_path = format!("{}/{}.class", target_dir, class_name);
+    if let Some(dir) = Path::new(&out_path).parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = fs::File::create(&out_path)?;
     file.write_all(&[0xCA, 0xFE, 0xBA, 0xBE, 0x00, 0x00, 0x00, 0x31])?;
     file.write_all(&kujav.cp.to_bytes())?;
     file.write_all(&[0x00, 0x21])?;
     file.write_all(&this_c.to_be_bytes())?;
     file.write_all(&super_c.to_be_bytes())?;
-    file.write_all(&[0x00, 0x00, 0x00, 0x00])?;
+    file.write_all(&[0x00, 0x00])?;
+
+    file.write_all(&(kujav.fields.len() as u16).to_be_bytes())?;
+    for field in &kujav.fields {
+        write_field(&mut file, field)?;
+    }
 
     let num_methods = (1 + kujav.methods.len()) as u16;
     file.write_all(&num_methods.to_be_bytes())?;
@@ -53,35 +109,51 @@ pub fn compile_to_class(class_name: &str, source: &str, out_path: &str) -> Kujav
     }
 
     file.write_all(&[0x00, 0x00])?;
-    Ok(())
+    Ok(class_name.to_string())
 }
 
-pub fn package_jar(cfg: &KujavToml, class_path: &str, jar_path: &str) -> KujavResult<()> {
+// `class_name` es el nombre interno devuelto por compile_to_class
+pub fn package_jar(
+    cfg: &KujavToml,
+    target_dir: &str,
+    class_name: &str,
+    jar_path: &str,
+) -> KujavResult<()> {
+    let main_class = class_name.replace('/', ".");
     let file = fs::File::create(jar_path)?;
     let mut zip = zip::ZipWriter::new(file);
     let options = FileOptions::default();
 
     zip.start_file("META-INF/MANIFEST.MF", options)
         .map_err(|e| KujavError::bytecode(e.to_string()))?;
-    let manifest = format!(
-        "Manifest-Version: 1.0\nMain-Class: {}\n\n",
-        cfg.package.name
-    );
+    let manifest = format!("Manifest-Version: 1.0\nMain-Class: {}\n\n", main_class);
     zip.write_all(manifest.as_bytes())?;
 
     if let Some(mc) = &cfg.minecraft {
+        // Sin main_class se usa la clase generada; si se indica, debe coincidir con ella
+        let plugin_main = if mc.main_class.is_empty() {
+            &main_class
+        } else {
+            &mc.main_class
+        };
+        if *plugin_main != main_class {
+            return Err(KujavError::toml(format!(
+                "[minecraft] main_class '{}' does not match the compiled class '{}'",
+                mc.main_class, main_class
+            )));
+        }
         zip.start_file("plugin.yml", options)
             .map_err(|e| KujavError::bytecode(e.to_string()))?;
         let plugin_yml = format!(
             "name: {}\nversion: {}\nmain: {}\napi-version: {}\n",
-            mc.plugin_name, mc.plugin_version, mc.main_class, mc.api
+            mc.plugin_name, mc.plugin_version, plugin_main, mc.api
         );
         zip.write_all(plugin_yml.as_bytes())?;
     }
 
-    zip.start_file(format!("{}.class", cfg.package.name), options)
+    zip.start_file(format!("{}.class", class_name), options)
         .map_err(|e| KujavError::bytecode(e.to_string()))?;
-    zip.write_all(&fs::read(class_path)?)?;
+    zip.write_all(&fs::read(format!("{}/{}.class", target_dir, class_name))?)?;
     zip.finish()
         .map_err(|e| KujavError::bytecode(e.to_string()))?;
     Ok(())
@@ -102,26 +174,50 @@ fn write_main_method(
 
     let main_len: u32 = 12 + kujav.current_bytecode.len() as u32;
     file.write_all(&main_len.to_be_bytes())?;
-    file.write_all(&[0x00, 0x0A, 0x00, 0x0A])?;
+    file.write_all(&kujav.max_stack(&kujav.current_bytecode).to_be_bytes())?;
+    file.write_all(&(kujav.max_slot as u16).max(10).to_be_bytes())?;
     file.write_all(&(kujav.current_bytecode.len() as u32).to_be_bytes())?;
     file.write_all(&kujav.current_bytecode)?;
     file.write_all(&[0x00, 0x00, 0x00, 0x00])?;
     Ok(())
 }
 
+fn write_field(file: &mut fs::File, f: &FieldInfo) -> KujavResult<()> {
+    file.write_all(&f.access_flags.to_be_bytes())?;
+    file.write_all(&f.name_idx.to_be_bytes())?;
+    file.write_all(&f.sig_idx.to_be_bytes())?;
+    write_attributes(file, &f.attributes)
+}
+
+fn write_attributes(file: &mut fs::File, attributes: &[(u16, Vec<u8>)]) -> KujavResult<()> {
+    file.write_all(&(attributes.len() as u16).to_be_bytes())?;
+    for (name_idx, data) in attributes {
+        file.write_all(&name_idx.to_be_bytes())?;
+        file.write_all(&(data.len() as u32).to_be_bytes())?;
+        file.write_all(data)?;
+    }
+    Ok(())
+}
+
 fn write_custom_method(file: &mut fs::File, m: &MethodInfo, c_a: u16) -> KujavResult<()> {
-    file.write_all(&[0x00, 0x09])?;
+    file.write_all(&m.access_flags.to_be_bytes())?;
     file.write_all(&m.name_idx.to_be_bytes())?;
     file.write_all(&m.sig_idx.to_be_bytes())?;
-    file.write_all(&[0x00, 0x01])?;
+    file.write_all(&(1 + m.attributes.len() as u16).to_be_bytes())?;
     file.write_all(&c_a.to_be_bytes())?;
 
     let attr_len: u32 = 12 + m.bytecode.len() as u32;
     file.write_all(&attr_len.to_be_bytes())?;
-    file.write_all(&[0x00, 0x0A])?;
+    file.write_all(&m.max_stack.to_be_bytes())?;
     file.write_all(&m.max_locals.to_be_bytes())?;
     file.write_all(&(m.bytecode.len() as u32).to_be_bytes())?;
     file.write_all(&m.bytecode)?;
     file.write_all(&[0x00, 0x00, 0x00, 0x00])?;
+
+    for (name_idx, data) in &m.attributes {
+        file.write_all(&name_idx.to_be_bytes())?;
+        file.write_all(&(data.len() as u32).to_be_bytes())?;
+        file.write_all(data)?;
+    }
     Ok(())
 }
@@ -1,132 +1,1631 @@
 // src/compiler/semantics.rs
+use crate::compiler::annotations::resolve_annotation;
+use crate::compiler::assembler::{self, VType};
+use crate::compiler::calls::{FunctionSig, MethodCall, jvm_name, resolve_args, resolve_method};
+use crate::compiler::constants::{ConstValue, eval_const};
+use crate::compiler::flow;
+use crate::compiler::ir::{Place, TExpr, TExprKind, TStmt, VarId};
+use crate::compiler::java_symbols::{JavaMethod, JavaSymbols};
+use crate::compiler::lints::{ALLOW, Lint, allowed_lints};
 use crate::compiler::types::KType;
-use crate::parser::ast::{Expr, Stmt};
-use std::collections::HashMap;
+use crate::errors::KujavWarning;
+use crate::parser::ast::{Annotation, Arg, Expr, Param, Stmt, Visibility};
+use crate::reader::class_index::ClassIndex;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+type Declared = (String, Option<KType>, Option<VarId>, Vec<Lint>); // (Nombre, Símbolo que ocultaba, Local que ocultaba, @allow al declararla)
+
+// Un bloque, un bucle o el cuerpo de una función. Guarda lo que había en `symbols`
+// antes de cada declaración para restaurarlo al cerrarse
+struct Scope {
+    function: bool, // Las locales de los bloques de fuera no son visibles
+    declared: Vec<Declared>,
+    hidden: Vec<(String, KType)>, // Locales de fuera retiradas al entrar en una función
+    hidden_locals: HashMap<String, VarId>,
+    used: RefCell<HashSet<String>>, // Locales del bloque que se han leído
+}
+
+// Estado que `if x is T` / `while x is T` sustituye mientras dura el cuerpo
+struct Narrowing {
+    name: String,
+    previous: Option<KType>,
+    previous_base: Option<(Option<VarId>, KType)>,
+}
 
 pub struct SemanticAnalyzer {
     pub symbols: HashMap<String, KType>,
+    pub java: JavaSymbols,
+    pub constants: HashMap<String, ConstValue>,
+    pub globals: HashMap<String, KType>, // `local` a nivel de módulo -> campos estáticos
+    pub functions: HashMap<String, FunctionSig>,
+    locals: HashMap<String, VarId>, // Locales visibles: nombre -> símbolo
+    narrowed: HashMap<String, (Option<VarId>, KType)>, // Estrechadas por `is`: (local, tipo declarado)
+    next_var: VarId,
+    scopes: Vec<Scope>, // Bloques abiertos, del más externo al más interno
+    closed_locals: HashSet<String>, // Locales cuyo bloque ya terminó, para explicar el error
+    pub warnings: Vec<KujavWarning>,
+    allowed: Vec<Lint>, // Lints silenciados por los @allow que rodean al código actual
+    called: RefCell<HashSet<String>>, // Funciones Kujav llamadas desde algún sitio
+    private_functions: Vec<String>, // Privadas o anidadas: sin llamadas no se usan
+    in_function: bool,
+    return_type: Option<KType>, // Retorno declarado de la función que se analiza
+    depth: usize,
 }
 
 impl SemanticAnalyzer {
-    pub fn new() -> Self {
+    pub fn new(classes: ClassIndex) -> Self {
         Self {
             symbols: HashMap::new(),
+            java: JavaSymbols::new(classes),
+            constants: HashMap::new(),
+            globals: HashMap::new(),
+            functions: HashMap::new(),
+            locals: HashMap::new(),
+            narrowed: HashMap::new(),
+            next_var: 0,
+            scopes: Vec::new(),
+            closed_locals: HashSet::new(),
+            warnings: Vec::new(),
+            allowed: Vec::new(),
+            called: RefCell::new(HashSet::new()),
+            private_functions: Vec::new(),
+            in_function: false,
+            return_type: None,
+            depth: 0,
+        }
+    }
+
+    // Sin índice cargado (p. ej. sin JDK) no se pueden validar los tipos externos
+    fn check_type(&self, t: &KType) -> Result<(), String> {
+        match t {
+            KType::Array(inner) | KType::List(inner) | KType::Set(inner) => self.check_type(inner),
+            KType::Map(k, v) => {
+                self.check_type(k)?;
+                self.check_type(v)
+            }
+            KType::Tuple(elems) => elems.iter().try_for_each(|e| match e {
+                KType::Void => Err("Una tupla no puede contener Void".into()),
+                _ => self.check_type(e),
+            }),
+            KType::Custom(name)
+                if !self.java.classes.is_empty() && self.java.resolve(name).is_none() =>
+            {
+                Err(format!(
+                    "Tipo '{}' no encontrado en el classpath ni en la JDK",
+                    name
+                ))
+            }
+            _ => Ok(()),
         }
     }
 
-    pub fn analyze(&mut self, ast: &[Stmt]) -> Result<(), String> {
-        for stmt in ast {
+    fn check_annotations(&self, annotations: &[Annotation], target: &str) -> Result<(), String> {
+        allowed_lints(annotations)?;
+        for ann in annotations.iter().filter(|a| a.name != ALLOW) {
+            resolve_annotation(ann, &self.java, Some(target))?;
+        }
+        Ok(())
+    }
+
+    // Comprueba el módulo y devuelve su representación tipada para el codegen
+    pub fn analyze(&mut self, ast: &[Stmt]) -> Result<Vec<TStmt>, String> {
+        // El paquete y los imports van al principio del archivo y las firmas los necesitan
+        let header = ast
+            .iter()
+            .position(|s| !matches!(s, Stmt::Package(_) | Stmt::Import(_)))
+            .unwrap_or(ast.len());
+        for stmt in &ast[..header] {
             self.check_stmt(stmt)?;
         }
+        flow::check_assignments(&ast[header..])?;
+        self.declare_functions(&ast[header..])?;
+        let mut program = Vec::new();
+        for stmt in &ast[header..] {
+            program.extend(self.check_stmt(stmt)?);
+        }
+        self.warn_unreachable(&ast[header..]);
+        self.warn_unused_items();
+        Ok(program)
+    }
+
+    // Primera pasada: registra las firmas del nivel superior para poder llamar a una
+    // función antes de su definición. Las constantes se evalúan aquí solo para los
+    // valores por defecto; sus errores se informan en la segunda pasada
+    fn declare_functions(&mut self, items: &[Stmt]) -> Result<(), String> {
+        let mut constants = HashMap::new();
+        for stmt in items {
+            match stmt {
+                Stmt::Const(name, expr, ..) => {
+                    if let Ok(value) = eval_const(expr, &constants) {
+                        constants.insert(name.clone(), value);
+                    }
+                }
+                Stmt::Function(name, params, _, ret_type, annotations, ..) => {
+                    self.declare_function(name, params, ret_type, annotations, &constants)?;
+                }
+                _ => {}
+            }
+        }
         Ok(())
     }
 
-    pub fn check_stmt(&mut self, stmt: &Stmt) -> Result<(), String> {
+    fn declare_function(
+        &mut self,
+        name: &str,
+        params: &[Param],
+        ret_type: &KType,
+        annotations: &[Annotation],
+        constants: &HashMap<String, ConstValue>,
+    ) -> Result<(), String> {
+        if name == "len" {
+            return Err("'len' es una función predefinida y no puede redefinirse".into());
+        }
+        if self.functions.contains_key(name) {
+            return Err(format!("La función '{}' ya está declarada", name));
+        }
+        if name.contains('.') && params.first().is_none_or(|(p, ..)| p != "self") {
+            return Err(format!(
+                "La extensión '{}' debe recibir 'self' como primer parámetro",
+                name
+            ));
+        }
+        self.check_type(ret_type)?;
+        self.check_annotations(annotations, "METHOD")?;
+        if name == "main" {
+            Self::check_entry_point(params, ret_type)?;
+        }
+        let sig = self.function_sig(name, params, ret_type, constants)?;
+        // Las extensiones comparten espacio de nombres con las funciones en el class file.
+        // Cada función genera un método por cada sobrecarga de sus valores por defecto
+        let descriptors = |s: &FunctionSig| -> Vec<String> {
+            let params: Vec<String> = s
+                .params
+                .iter()
+                .map(|(_, t, _)| self.java.type_sig(t))
+                .collect();
+            (s.required()..=params.len())
+                .map(|count| params[..count].concat())
+                .collect()
+        };
+        let own = descriptors(&sig);
+        if let Some((other, desc)) = self.functions.iter().find_map(|(other, other_sig)| {
+            if jvm_name(other) != jvm_name(name) {
+                return None;
+            }
+            let theirs = descriptors(other_sig);
+            own.iter()
+                .find(|d| theirs.contains(d))
+                .map(|d| (other, d.clone()))
+        }) {
+            return Err(format!(
+                "'{}' y '{}' se compilan al mismo método estático '{}({})'",
+                name,
+                other,
+                jvm_name(name),
+                desc
+            ));
+        }
+        self.functions.insert(name.to_string(), sig);
+        Ok(())
+    }
+
+    // Sentencias tipadas equivalentes: ninguna (package, import), una, o varias
+    // (la declaración de una global y su inicialización)
+    pub fn check_stmt(&mut self, stmt: &Stmt) -> Result<Vec<TStmt>, String> {
         match stmt {
-            Stmt::Let(name, expr, _type_ann) => {
-                let t = self.check_expr(expr)?;
-                self.symbols.insert(name.clone(), t);
-                Ok(())
+            Stmt::Package(name) => {
+                if name == "java" || name.starts_with("java.") {
+                    return Err(format!("El paquete '{}' está reservado por la JVM", name));
+                }
+                self.java.set_package(name);
+                Ok(Vec::new())
             }
-            Stmt::Print(expr) => {
-                self.check_expr(expr)?;
-                Ok(())
+            Stmt::Import(path) => {
+                self.java.add_import(path)?;
+                Ok(Vec::new())
             }
-            Stmt::Function(name, params, body, ret_type) => {
-                self.symbols.insert(name.clone(), ret_type.clone());
-
-                // --- SOLUCIÓN AL ERROR: Gestionar el ámbito de los parámetros ---
-                let old_symbols = self.symbols.clone(); // Guardamos ámbito superior
-                for (p_name, p_type) in params {
-                    self.symbols.insert(p_name.clone(), p_type.clone());
+            Stmt::Cfg(..) => unreachable!("los @cfg se resuelven antes del análisis"),
+            Stmt::Let(name, expr, let_type, annotations, visibility, _) => {
+                if visibility.is_some() && !self.is_new_global(name) {
+                    return Err(format!(
+                        "La visibilidad solo se indica al declarar una global del módulo ('{}')",
+                        name
+                    ));
+                }
+                if let Some(t) = let_type {
+                    self.check_type(t)?;
+                }
+                let value =
+                    self.check_initializer(std::slice::from_ref(name), expr, let_type.as_ref())?;
+                let allowed = self.allowed.len();
+                self.allowed.extend(allowed_lints(annotations)?);
+                let result = self.bind(name, value.ktype.clone(), annotations, let_type.is_some());
+                self.allowed.truncate(allowed);
+                let (place, new_global) = result?;
+                let t = place.ktype().clone();
+                let store = TStmt::Let(place, value.convert_to(&t));
+                if !new_global {
+                    return Ok(vec![store]);
+                }
+                // Solo un valor constante se asigna en <clinit>; el resto se asigna en
+                // main, en su sitio, para ver lo que ya ejecutó el código del módulo
+                let init = if eval_const(expr, &self.constants).is_ok() {
+                    TStmt::StaticInit(Box::new(store))
+                } else {
+                    store
+                };
+                Ok(vec![
+                    TStmt::Global(name.clone(), t, annotations.clone(), *visibility),
+                    init,
+                ])
+            }
+            Stmt::LetMulti(names, expr, _) => {
+                let value = self.check_initializer(names, expr, None)?;
+                let KType::Tuple(elems) = value.ktype.clone() else {
+                    return Err(format!(
+                        "Se esperaban {} valores pero la expresión devuelve un solo {}",
+                        names.len(),
+                        value.ktype
+                    ));
+                };
+                if elems.len() != names.len() {
+                    return Err(format!(
+                        "Se esperaban {} valores pero la expresión devuelve {}",
+                        names.len(),
+                        elems.len()
+                    ));
+                }
+                let mut places = Vec::new();
+                let mut new_globals = Vec::new();
+                for (name, t) in names.iter().zip(elems) {
+                    let (place, new_global) = self.bind(name, t, &[], false)?;
+                    if new_global {
+                        new_globals.push(TStmt::Global(
+                            name.clone(),
+                            place.ktype().clone(),
+                            Vec::new(),
+                            None,
+                        ));
+                    }
+                    places.push(place);
+                }
+                let store = TStmt::LetMulti(places, value);
+                if new_globals.is_empty() {
+                    return Ok(vec![store]);
+                }
+                new_globals.push(store);
+                Ok(new_globals)
+            }
+            Stmt::Const(name, expr, const_type, annotations, visibility) => {
+                if self.symbols.contains_key(name) {
+                    return Err(format!("'{}' ya está declarado", name));
+                }
+                if let Some(t) = const_type {
+                    self.check_type(t)?;
+                }
+                self.check_annotations(annotations, "FIELD")?;
+                let value = eval_const(expr, &self.constants).map_err(|e| {
+                    format!(
+                        "El valor de la constante '{}' debe ser una expresión constante: {}",
+                        name, e
+                    )
+                })?;
+                let t = value.ktype();
+                if let Some(declared) = const_type
+                    && *declared != t
+                {
+                    return Err(format!(
+                        "La constante '{}' se declara como {} pero su valor es {}",
+                        name, declared, t
+                    ));
+                }
+                self.symbols.insert(name.clone(), t);
+                self.constants.insert(name.clone(), value.clone());
+                Ok(vec![TStmt::Const(
+                    name.clone(),
+                    value,
+                    annotations.clone(),
+                    *visibility,
+                )])
+            }
+            Stmt::Print(expr) => Ok(vec![TStmt::Print(self.check_expr(expr)?)]),
+            Stmt::Function(name, params, body, ret_type, annotations, _, visibility) => {
+                if visibility.is_some() && (self.in_function || self.depth > 0) {
+                    return Err(format!(
+                        "La visibilidad solo se permite en funciones del nivel superior ('{}')",
+                        name
+                    ));
+                }
+                // Las del nivel superior ya se declararon en la primera pasada
+                let nested = self.in_function || self.depth > 0;
+                if nested && name == "main" {
+                    return Err(
+                        "La función 'main' solo puede declararse en el nivel superior".into(),
+                    );
+                }
+                if nested {
+                    let constants = self.constants.clone();
+                    self.declare_function(name, params, ret_type, annotations, &constants)?;
+                }
+                let allowed = self.allowed.len();
+                self.allowed.extend(allowed_lints(annotations)?);
+                // Solo el código Kujav puede llamar a las privadas y a las anidadas
+                if (nested || *visibility == Some(Visibility::Private))
+                    && !self.allows(Lint::UnusedFunctions)
+                {
+                    self.private_functions.push(name.clone());
                 }
 
-                for s in body {
-                    self.check_stmt(s)?;
+                // Dentro de la función solo se ven globales, constantes, funciones y sus parámetros
+                self.push_scope(true);
+                let mut typed_params = Vec::new();
+                for (p_name, p_type, p_annotations, _, _) in params {
+                    let id = self.declare_local(p_name, p_type.clone());
+                    self.mark_used(p_name);
+                    typed_params.push((id, p_type.clone(), p_annotations.clone()));
                 }
 
-                self.symbols = old_symbols; // Restauramos el ámbito original
-                Ok(())
+                let was_in_function = std::mem::replace(&mut self.in_function, true);
+                let outer_return = self.return_type.replace(ret_type.clone());
+                let result = self.check_block(body).and_then(|typed_body| {
+                    if *ret_type != KType::Void && !flow::always_returns(body) {
+                        return Err(format!(
+                            "La función '{}' devuelve {}, pero algún camino llega al final sin 'return'",
+                            name, ret_type
+                        ));
+                    }
+                    Ok(typed_body)
+                });
+                self.in_function = was_in_function;
+                self.return_type = outer_return;
+                self.pop_scope();
+                self.allowed.truncate(allowed);
+                Ok(vec![TStmt::Function(
+                    name.clone(),
+                    typed_params,
+                    result?,
+                    ret_type.clone(),
+                    annotations.clone(),
+                    *visibility,
+                )])
             }
             Stmt::If(cond, if_body, else_body) => {
-                if self.check_expr(cond)? != KType::Bool {
+                let typed_cond = self.check_expr(cond)?;
+                if typed_cond.ktype != KType::Bool {
                     return Err("Condición debe ser Bool".into());
                 }
-                for s in if_body {
-                    self.check_stmt(s)?;
-                }
-                if let Some(eb) = else_body {
-                    for s in eb {
-                        self.check_stmt(s)?;
-                    }
-                }
-                Ok(())
+                self.warn_constant_condition(cond);
+                let then_body = self.check_narrowed(cond, if_body)?;
+                let else_body = match else_body {
+                    Some(eb) => Some(self.check_block(eb)?),
+                    None => None,
+                };
+                Ok(vec![TStmt::If(
+                    typed_cond,
+                    then_body,
+                    else_body,
+                    flow::always_returns(if_body),
+                )])
             }
             Stmt::While(cond, body) => {
-                if self.check_expr(cond)? != KType::Bool {
+                let typed_cond = self.check_expr(cond)?;
+                if typed_cond.ktype != KType::Bool {
                     return Err("Condición debe ser Bool".into());
                 }
-                for s in body {
-                    self.check_stmt(s)?;
+                // `while true` es la forma de escribir un bucle que sale con return
+                if !matches!(cond, Expr::Boolean(true)) {
+                    self.warn_constant_condition(cond);
                 }
-                Ok(())
+                let typed_body = self.check_narrowed(cond, body)?;
+                Ok(vec![TStmt::While(typed_cond, typed_body)])
+            }
+            Stmt::For(var, iterable, body, _) => {
+                let typed_iterable = match iterable {
+                    Expr::Range(start, end, inclusive) => {
+                        self.check_range(start, end, *inclusive)?
+                    }
+                    _ => self.check_expr(iterable)?,
+                };
+                let elem_t = match &typed_iterable.ktype {
+                    KType::Array(t) | KType::List(t) | KType::Set(t) => (**t).clone(),
+                    KType::Map(k, _) => (**k).clone(), // Se recorren las claves
+                    other => {
+                        return Err(format!(
+                            "No se puede recorrer con 'for' un valor de tipo {}",
+                            other
+                        ));
+                    }
+                };
+                // La variable del bucle solo existe dentro del cuerpo
+                self.push_scope(false);
+                self.warn_shadowing(var);
+                let id = self.declare_local(var, elem_t);
+                let result = self.check_block(body);
+                self.pop_scope();
+                Ok(vec![TStmt::For(id, typed_iterable, result?)])
             }
             Stmt::Return(maybe_expr) => {
-                if let Some(expr) = maybe_expr {
-                    self.check_expr(expr)?;
+                let expected = match &self.return_type {
+                    Some(t) if self.in_function => t.clone(),
+                    _ => KType::Void, // El código del módulo va en main(String[]): void
+                };
+                match (maybe_expr, &expected) {
+                    (None, KType::Void) => Ok(vec![TStmt::Return(None)]),
+                    (None, t) => Err(format!(
+                        "'return' sin valor en una función que devuelve {}",
+                        t
+                    )),
+                    (Some(expr), KType::Void) => {
+                        let t = self.check_expr(expr)?.ktype;
+                        if self.in_function {
+                            Err(format!(
+                                "La función no devuelve nada (Void), pero el return tiene un valor de tipo {}",
+                                t
+                            ))
+                        } else {
+                            Err(format!(
+                                "'return' con un valor de tipo {} fuera de una función",
+                                t
+                            ))
+                        }
+                    }
+                    (Some(expr), expected) => {
+                        let value = self.check_expr(expr)?;
+                        self.check_tuple_return(&value.ktype)?;
+                        if matches!(value.ktype, KType::Tuple(_)) {
+                            return Ok(vec![TStmt::Return(Some(value))]);
+                        }
+                        if !self.converts(expected, &value.ktype) {
+                            return Err(format!(
+                                "El return devuelve {}, pero la función declara {}",
+                                value.ktype, expected
+                            ));
+                        }
+                        Ok(vec![TStmt::Return(Some(value.convert_to(expected)))])
+                    }
                 }
-                Ok(())
             }
-            Stmt::Call(_, args) => {
-                for a in args {
-                    self.check_expr(a)?;
+            Stmt::Call(name, args) => Ok(vec![TStmt::Expr(self.check_call(name, args)?)]),
+            Stmt::Assert(cond, message) => {
+                let typed_cond = self.check_expr(cond)?;
+                if typed_cond.ktype != KType::Bool {
+                    return Err(format!(
+                        "La condición de assert debe ser Bool, se encontró {}",
+                        typed_cond.ktype
+                    ));
                 }
-                Ok(())
+                let typed_message = match message {
+                    Some(msg) => {
+                        let typed = self.check_expr(msg)?;
+                        if typed.ktype == KType::Void {
+                            return Err("El mensaje de assert no puede ser Void".into());
+                        }
+                        Some(typed)
+                    }
+                    None => None,
+                };
+                Ok(vec![TStmt::Assert(typed_cond, typed_message)])
+            }
+            Stmt::Asm(body) => {
+                let items = assembler::parse(body)?;
+                let ret = match &self.return_type {
+                    Some(t) if self.in_function => VType::from_desc(&self.java.type_sig(t)),
+                    _ => None, // El código del módulo va en main(String[]): void
+                };
+                let local = |name: &str| match self.lookup(name).and(self.declared_type(name)) {
+                    Some(t) if self.local_scope(name).is_some() => {
+                        Ok(VType::from_desc(&self.java.type_sig(t)).unwrap_or(VType::Ref))
+                    }
+                    Some(_) => Err(format!(
+                        "'{}' no es una variable local (las globales se leen con getstatic)",
+                        name
+                    )),
+                    None => Err(self.undefined(name)),
+                };
+                assembler::validate(&items, local, ret)?;
+                Ok(vec![TStmt::Asm(items, self.locals.clone())])
             }
             Stmt::IndexAssign(name, idx, val) => {
-                self.check_expr(idx)?;
-                let val_t = self.check_expr(val)?;
-                match self.symbols.get(name) {
-                    Some(KType::Array(inner)) if **inner == val_t => Ok(()),
-                    _ => Err(format!("Error de tipo en arreglo '{}'", name)),
+                let (container, index, elem_t) = self.check_index(name, idx)?;
+                let value = self.check_expr(val)?;
+                if !self.accepts(&elem_t, &value.ktype) {
+                    return Err(format!(
+                        "No se puede guardar un {} en '{}', que contiene {}",
+                        value.ktype, name, elem_t
+                    ));
                 }
+                Ok(vec![TStmt::IndexAssign(
+                    container,
+                    index,
+                    value.convert_to(&elem_t),
+                )])
             }
         }
     }
 
-    pub fn check_expr(&self, expr: &Expr) -> Result<KType, String> {
-        match expr {
-            Expr::Number(_) => Ok(KType::Int),
-            Expr::String(_) => Ok(KType::String),
-            Expr::Boolean(_) => Ok(KType::Bool),
-            Expr::Identifier(n) => self
-                .symbols
-                .get(n)
-                .cloned()
-                .ok_or(format!("Variable '{}' no definida", n)),
-            Expr::Binary(l, op, r) => {
-                let lt = self.check_expr(l)?;
-                let rt = self.check_expr(r)?;
-                if op == "+" && (lt == KType::String || rt == KType::String) {
-                    Ok(KType::String)
-                } else {
-                    Ok(lt)
+    fn check_block(&mut self, body: &[Stmt]) -> Result<Vec<TStmt>, String> {
+        self.depth += 1;
+        self.push_scope(false);
+        let mut typed = Vec::new();
+        let result = body.iter().try_for_each(|s| {
+            typed.extend(self.check_stmt(s)?);
+            Ok(())
+        });
+        if result.is_ok() {
+            self.warn_unreachable(body);
+        }
+        self.pop_scope();
+        self.depth -= 1;
+        result.map(|_| typed)
+    }
+
+    // Cuerpo de un if/while; tras `x is T` las lecturas de `x` ven el tipo T
+    fn check_narrowed(&mut self, cond: &Expr, body: &[Stmt]) -> Result<Vec<TStmt>, String> {
+        let narrowing = self.narrow(cond);
+        let result = self.check_block(body);
+        self.restore_narrowing(narrowing);
+        result
+    }
+
+    // Al entrar en una función se retiran las locales visibles de fuera
+    fn push_scope(&mut self, function: bool) {
+        let mut hidden = Vec::new();
+        let mut hidden_locals = HashMap::new();
+        if function {
+            let visible: Vec<String> = self.visible_locals().map(String::from).collect();
+            for name in visible {
+                if let Some(t) = self.symbols.remove(&name) {
+                    hidden.push((name, t));
+                }
+            }
+            hidden_locals = std::mem::take(&mut self.locals);
+        }
+        self.scopes.push(Scope {
+            function,
+            declared: Vec::new(),
+            hidden,
+            hidden_locals,
+            used: RefCell::new(HashSet::new()),
+        });
+    }
+
+    fn pop_scope(&mut self) {
+        let scope = self.scopes.pop().expect("pop_scope sin push_scope");
+        let used = scope.used.into_inner();
+        // El aviso se decide con los @allow que rodeaban a la declaración
+        for (name, _, _, allowed) in scope.declared.iter().filter(|(n, ..)| !used.contains(n)) {
+            let current = std::mem::replace(&mut self.allowed, allowed.clone());
+            self.warn(
+                Lint::UnusedVariables,
+                format!("La variable '{}' se declara pero nunca se lee", name),
+            );
+            self.allowed = current;
+        }
+        for (name, previous, previous_local, _) in scope.declared.into_iter().rev() {
+            match previous_local {
+                Some(id) => self.locals.insert(name.clone(), id),
+                None => self.locals.remove(&name),
+            };
+            match previous {
+                Some(t) => {
+                    self.symbols.insert(name, t);
                 }
+                None => {
+                    self.symbols.remove(&name);
+                    self.closed_locals.insert(name);
+                }
+            }
+        }
+        self.symbols.extend(scope.hidden);
+        if scope.function {
+            self.locals = scope.hidden_locals;
+        }
+    }
+
+    // Bloques de la función actual (o del código del módulo), del más interno al más externo
+    fn function_scopes(&self) -> impl Iterator<Item = &Scope> {
+        let base = self.scopes.iter().rposition(|s| s.function).unwrap_or(0);
+        self.scopes[base..].iter().rev()
+    }
+
+    fn visible_locals(&self) -> impl Iterator<Item = &str> {
+        self.function_scopes()
+            .flat_map(|s| s.declared.iter().map(|(name, ..)| name.as_str()))
+    }
+
+    // Profundidad (0 = bloque actual) del bloque que declara `name`, si es una local visible
+    fn local_scope(&self, name: &str) -> Option<usize> {
+        self.function_scopes()
+            .position(|s| s.declared.iter().any(|(n, ..)| n == name))
+    }
+
+    // Nueva local en el bloque actual, con un símbolo propio; redeclararla en el
+    // mismo bloque la sustituye hasta el final del bloque
+    fn declare_local(&mut self, name: &str, t: KType) -> VarId {
+        let id = self.next_var;
+        self.next_var += 1;
+        let previous = self.symbols.insert(name.to_string(), t);
+        let previous_local = self.locals.insert(name.to_string(), id);
+        let scope = self.scopes.last_mut().expect("local fuera de un bloque");
+        if !scope.declared.iter().any(|(n, ..)| n == name) {
+            scope.declared.push((
+                name.to_string(),
+                previous,
+                previous_local,
+                self.allowed.clone(),
+            ));
+        }
+        // `_nombre` marca una variable que se ignora a propósito
+        if name.starts_with('_') {
+            self.mark_used(name);
+        }
+        id
+    }
+
+    // Tipo de un nombre visible; si es una local, cuenta como leída
+    fn lookup(&self, name: &str) -> Option<&KType> {
+        self.mark_used(name);
+        self.symbols.get(name)
+    }
+
+    // Tipo con el que se declaró la variable, aunque un `is` la esté estrechando
+    fn declared_type(&self, name: &str) -> Option<&KType> {
+        match self.narrowed.get(name) {
+            Some((local, base)) if *local == self.locals.get(name).copied() => Some(base),
+            _ => self.symbols.get(name),
+        }
+    }
+
+    // Lectura de un nombre visible: la local que lo declara, la constante o la global.
+    // Si está estrechada se lee con su tipo declarado y se convierte (checkcast)
+    fn resolve(&self, name: &str) -> Option<TExpr> {
+        let t = self.lookup(name)?.clone();
+        let base = self.declared_type(name)?.clone();
+        let read = if let Some(&id) = self.locals.get(name) {
+            TExpr::new(TExprKind::Local(id), base)
+        } else if let Some(value) = self.constants.get(name) {
+            return Some(TExpr::new(TExprKind::Const(value.clone()), t));
+        } else {
+            TExpr::new(TExprKind::Global(name.to_string()), base)
+        };
+        Some(if read.ktype == t {
+            read
+        } else {
+            TExpr::new(TExprKind::Cast(Box::new(read), false), t)
+        })
+    }
+
+    fn mark_used(&self, name: &str) {
+        if let Some(scope) = self
+            .function_scopes()
+            .find(|s| s.declared.iter().any(|(n, ..)| n == name))
+        {
+            scope.used.borrow_mut().insert(name.to_string());
+        }
+    }
+
+    fn allows(&self, lint: Lint) -> bool {
+        self.allowed.contains(&lint)
+    }
+
+    // Los avisos de un lint silenciado por un @allow que rodea al código no se registran
+    fn warn(&mut self, lint: Lint, message: String) {
+        if !self.allows(lint) {
+            self.warnings.push(KujavWarning {
+                lint: lint.name(),
+                message,
+            });
+        }
+    }
+
+    fn warn_shadowing(&mut self, name: &str) {
+        if self.local_scope(name).is_some() {
+            self.warn(
+                Lint::Shadowing,
+                format!(
+                    "'{}' oculta a una variable local declarada en un bloque exterior",
+                    name
+                ),
+            );
+        }
+    }
+
+    // Lo que sigue a un return (o a un if/while/asm del que no se sale) no se ejecuta nunca
+    fn warn_unreachable(&mut self, body: &[Stmt]) {
+        let Some(pos) = body
+            .iter()
+            .position(|s| flow::always_returns(std::slice::from_ref(s)))
+        else {
+            return;
+        };
+        if pos + 1 < body.len() {
+            let after = match &body[pos] {
+                Stmt::Return(_) => "un 'return'",
+                Stmt::While(..) => "un 'while true'",
+                Stmt::If(..) => "un 'if' cuyas dos ramas retornan",
+                _ => "un bloque 'asm' que no llega a su final",
+            };
+            self.warn(
+                Lint::UnreachableCode,
+                format!(
+                    "Código inalcanzable: {} sentencia(s) después de {}",
+                    body.len() - pos - 1,
+                    after
+                ),
+            );
+        }
+    }
+
+    // Condición que se puede calcular al compilar: una de las ramas sobra
+    fn warn_constant_condition(&mut self, cond: &Expr) {
+        if let Ok(ConstValue::Bool(value)) = eval_const(cond, &self.constants) {
+            self.warn(
+                Lint::ConstantConditions,
+                format!("La condición siempre es {}", value),
+            );
+        }
+    }
+
+    // Al terminar el módulo: imports que no resolvieron nada y funciones privadas sin llamadas
+    fn warn_unused_items(&mut self) {
+        // Sin índice de clases no se resuelve ningún nombre
+        if !self.java.classes.is_empty() {
+            for import in self.java.unused_imports() {
+                self.warn(
+                    Lint::UnusedImports,
+                    format!("El import '{}' no se usa", import),
+                );
+            }
+        }
+        let called = self.called.borrow().clone();
+        for name in std::mem::take(&mut self.private_functions) {
+            if !called.contains(&name) {
+                self.warn(
+                    Lint::UnusedFunctions,
+                    format!("La función '{}' no se llama nunca", name),
+                );
             }
+        }
+    }
+
+    fn undefined(&self, name: &str) -> String {
+        if self.closed_locals.contains(name) {
+            format!("'{}' solo existe dentro del bloque donde se declaró", name)
+        } else {
+            format!("Variable '{}' no definida", name)
+        }
+    }
+
+    // Punto de entrada: sin parámetros o con los argumentos del programa, y devuelve
+    // el código de salida (Int) o nada
+    fn check_entry_point(params: &[Param], ret_type: &KType) -> Result<(), String> {
+        let params_ok = match params {
+            [] => true,
+            [(_, KType::Array(elem), _, None, _)] => **elem == KType::String,
+            _ => false,
+        };
+        if !params_ok || !matches!(ret_type, KType::Int | KType::Void) {
+            return Err(
+                "'main' debe declararse como 'function main()' o 'function main(args: String[])' y devolver Int o nada".into(),
+            );
+        }
+        Ok(())
+    }
+
+    // Tipos de los parámetros y sus valores por defecto, que deben ser constantes
+    // y quedar al final para poder generar las sobrecargas puente
+    fn function_sig(
+        &self,
+        name: &str,
+        params: &[Param],
+        ret: &KType,
+        constants: &HashMap<String, ConstValue>,
+    ) -> Result<FunctionSig, String> {
+        let mut sig_params = Vec::new();
+        let mut has_defaults = false;
+        let variadic = params.iter().any(|(_, _, _, _, v)| *v);
+        for (i, (p_name, p_type, p_annotations, default, p_variadic)) in params.iter().enumerate() {
+            if *p_type == KType::Void {
+                return Err(format!(
+                    "'self' sin tipo solo puede ser el primer parámetro de una función de extensión ('{}')",
+                    name
+                ));
+            }
+            self.check_type(p_type)?;
+            self.check_annotations(p_annotations, "PARAMETER")?;
+            if *p_variadic && i + 1 != params.len() {
+                return Err(format!(
+                    "El parámetro variádico '...{}' de '{}' debe ser el último",
+                    p_name, name
+                ));
+            }
+            if variadic && default.is_some() {
+                return Err(format!(
+                    "'{}' es variádica y no admite valores por defecto ('{}')",
+                    name, p_name
+                ));
+            }
+            let default = match default {
+                Some(expr) => {
+                    let value = eval_const(expr, constants).map_err(|e| {
+                        format!(
+                            "El valor por defecto de '{}' en '{}' debe ser una expresión constante: {}",
+                            p_name, name, e
+                        )
+                    })?;
+                    if value.ktype() != *p_type {
+                        return Err(format!(
+                            "El valor por defecto de '{}' es {}, pero el parámetro es {}",
+                            p_name,
+                            value.ktype(),
+                            p_type
+                        ));
+                    }
+                    has_defaults = true;
+                    Some(value)
+                }
+                None if has_defaults => {
+                    return Err(format!(
+                        "El parámetro '{}' de '{}' no tiene valor por defecto y va después de uno que sí lo tiene",
+                        p_name, name
+                    ));
+                }
+                None => None,
+            };
+            sig_params.push((p_name.clone(), p_type.clone(), default));
+        }
+        Ok(FunctionSig {
+            params: sig_params,
+            ret: ret.clone(),
+            variadic,
+        })
+    }
+
+    // Método de una variable, función del módulo (declarada antes o después) o método estático de Java
+    fn check_call(&self, name: &str, args: &[Arg]) -> Result<TExpr, String> {
+        // `valor.método(...)`: el primer segmento es una variable, no una clase
+        if let Some((receiver, method)) = name.split_once('.')
+            && !method.contains('.')
+            && let Some(receiver_value) = self.resolve(receiver)
+        {
+            return self.check_method_call(receiver, receiver_value, method, args);
+        }
+        if let Some(sig) = self.functions.get(name) {
+            return self.check_function_call(name, sig, args);
+        }
+        if let Some((class, method)) = name.rsplit_once('.') {
+            return self.check_java_call(class, method, args);
+        }
+        Err(format!("Función '{}' no definida", name))
+    }
+
+    // Los argumentos quedan en el orden de los parámetros, con los valores por
+    // defecto insertados, convertidos a su tipo y los variádicos empaquetados
+    fn check_function_call(
+        &self,
+        name: &str,
+        sig: &FunctionSig,
+        args: &[Arg],
+    ) -> Result<TExpr, String> {
+        self.called.borrow_mut().insert(name.to_string());
+        let values = resolve_args(name, sig, args)?;
+        let mut typed_args = Vec::new();
+        for (i, value) in values.iter().enumerate() {
+            match (&sig.params[i], value) {
+                ((_, array_t @ KType::Array(elem_t), _), Expr::ArrayLiteral(elems))
+                    if sig.variadic && i + 1 == values.len() =>
+                {
+                    // Un único argumento que ya es el arreglo se pasa sin empaquetar
+                    if elems.len() == 1 {
+                        let single = self.check_expr(&elems[0])?;
+                        if single.ktype == *array_t {
+                            typed_args.push(single);
+                            continue;
+                        }
+                    }
+                    let mut packed = Vec::new();
+                    for (n, e) in elems.iter().enumerate() {
+                        let typed = self.check_expr(e)?;
+                        if self.java.arg_cost(elem_t, &typed.ktype).is_none() {
+                            return Err(format!(
+                                "El argumento variádico {} de '{}' es {}, se esperaba {}",
+                                n + 1,
+                                name,
+                                typed.ktype,
+                                elem_t
+                            ));
+                        }
+                        packed.push(typed.convert_to(elem_t));
+                    }
+                    typed_args.push(TExpr::new(TExprKind::ArrayLiteral(packed), array_t.clone()));
+                }
+                ((p_name, p_type, _), _) => {
+                    let typed = self.check_expr(value)?;
+                    if !self.converts(p_type, &typed.ktype) {
+                        return Err(format!(
+                            "El argumento '{}' de '{}' es {}, se esperaba {}",
+                            p_name, name, typed.ktype, p_type
+                        ));
+                    }
+                    typed_args.push(typed.convert_to(p_type));
+                }
+            }
+        }
+        Ok(TExpr::new(
+            TExprKind::Call(name.to_string(), typed_args),
+            sig.ret.clone(),
+        ))
+    }
+
+    // Método de instancia Java o, si el tipo no lo tiene, extensión con el receptor como `self`
+    fn check_method_call(
+        &self,
+        receiver: &str,
+        receiver_value: TExpr,
+        method: &str,
+        args: &[Arg],
+    ) -> Result<TExpr, String> {
+        let mut typed_args = Vec::new();
+        for (_, value) in args {
+            typed_args.push(self.check_expr(value)?);
+        }
+        let arg_types: Vec<KType> = typed_args.iter().map(|a| a.ktype.clone()).collect();
+        match resolve_method(
+            &self.java,
+            &self.functions,
+            &receiver_value.ktype,
+            method,
+            &arg_types,
+        )? {
+            MethodCall::Java(m) => {
+                if let Some(arg_name) = args.iter().find_map(|(n, _)| n.as_ref()) {
+                    return Err(format!(
+                        "Los métodos Java no admiten argumentos con nombre ('{}' en {}.{})",
+                        arg_name, receiver, method
+                    ));
+                }
+                Ok(Self::java_call(Some(receiver_value), method, m, typed_args))
+            }
+            MethodCall::Extension(name) => {
+                let mut full_args = vec![(None, Expr::var(receiver))];
+                full_args.extend(args.iter().cloned());
+                self.check_function_call(&name, &self.functions[&name], &full_args)
+            }
+        }
+    }
+
+    // `Clase.método(args)`: método estático Java elegido por los tipos de los argumentos
+    fn check_java_call(&self, class: &str, method: &str, args: &[Arg]) -> Result<TExpr, String> {
+        let mut typed_args = Vec::new();
+        for (arg_name, value) in args {
+            if let Some(arg_name) = arg_name {
+                return Err(format!(
+                    "Los métodos Java no admiten argumentos con nombre ('{}' en {}.{})",
+                    arg_name, class, method
+                ));
+            }
+            typed_args.push(self.check_expr(value)?);
+        }
+        let arg_types: Vec<KType> = typed_args.iter().map(|a| a.ktype.clone()).collect();
+        let m = self.java.resolve_static(class, method, &arg_types)?;
+        Ok(Self::java_call(None, method, m, typed_args))
+    }
+
+    // Argumentos convertidos a los parámetros de la sobrecarga elegida; los
+    // sobrantes de un método varargs van en un arreglo nuevo
+    fn java_call(receiver: Option<TExpr>, method: &str, m: JavaMethod, args: Vec<TExpr>) -> TExpr {
+        let fixed = m.params.len() - m.packs_varargs as usize;
+        let mut args = args.into_iter();
+        let mut typed_args: Vec<TExpr> = m.params[..fixed]
+            .iter()
+            .zip(args.by_ref())
+            .map(|(param, arg)| arg.convert_to(param))
+            .collect();
+        if m.packs_varargs {
+            let array_t = m.params[fixed].clone();
+            let KType::Array(elem_t) = &array_t else {
+                unreachable!("el parámetro varargs de Java es un arreglo");
+            };
+            let packed = args.map(|arg| arg.convert_to(elem_t)).collect();
+            typed_args.push(TExpr::new(TExprKind::ArrayLiteral(packed), array_t.clone()));
+        }
+        let ret = m.ret.clone();
+        TExpr::new(
+            TExprKind::JavaCall(receiver.map(Box::new), method.to_string(), m, typed_args),
+            ret,
+        )
+    }
+
+    // `return a, b` debe coincidir en número y tipos con la tupla declarada
+    fn check_tuple_return(&self, t: &KType) -> Result<(), String> {
+        match (&self.return_type, t) {
+            (Some(KType::Tuple(expected)), KType::Tuple(actual)) => {
+                if expected.len() != actual.len() {
+                    return Err(format!(
+                        "Se esperaban {} valores en el return, se encontraron {}",
+                        expected.len(),
+                        actual.len()
+                    ));
+                }
+                for (i, (e, a)) in expected.iter().zip(actual).enumerate() {
+                    if !self.accepts(e, a) {
+                        return Err(format!(
+                            "El valor {} del return es {}, se esperaba {}",
+                            i + 1,
+                            a,
+                            e
+                        ));
+                    }
+                }
+                Ok(())
+            }
+            (Some(expected @ KType::Tuple(_)), other) => Err(format!(
+                "La función devuelve {}, no se puede devolver un solo {}",
+                expected, other
+            )),
+            (Some(expected), KType::Tuple(_)) => Err(format!(
+                "La función devuelve {}, no varios valores",
+                expected
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    // Fuera de funciones, el primer `local` del nivel superior crea una global
+    fn is_new_global(&self, name: &str) -> bool {
+        !self.in_function && self.depth == 0 && !self.globals.contains_key(name)
+    }
+
+    // Valor asignado a `names`, con el contexto de la global si es una global nueva
+    fn check_initializer(
+        &mut self,
+        names: &[String],
+        expr: &Expr,
+        expected: Option<&KType>,
+    ) -> Result<TExpr, String> {
+        for name in names {
+            if self.constants.contains_key(name) {
+                return Err(format!(
+                    "'{}' es una constante y no se puede reasignar",
+                    name
+                ));
+            }
+        }
+        let new_globals = names.iter().filter(|n| self.is_new_global(n)).count();
+        if new_globals == 0 {
+            return self.check_value(expr, expected);
+        }
+        if new_globals != names.len() {
+            return Err(format!(
+                "No se pueden mezclar globales nuevas y existentes en 'local {}'",
+                names.join(", ")
+            ));
+        }
+        // En el nivel superior no hay ningún bloque abierto: no hay locales visibles
+        self.check_value(expr, expected).map_err(|e| {
+            format!(
+                "En el inicializador de la global '{}': {}",
+                names.join(", "),
+                e
+            )
+        })
+    }
+
+    // Registra `name` con el tipo ya comprobado: local, global nueva o asignación.
+    // Sin tipo declarado, `local x = ...` sobre una local de un bloque exterior la
+    // asigna (así avanzan los contadores de los bucles); con tipo, declara otra que la oculta.
+    // Devuelve dónde se guarda el valor y si es una global nueva
+    fn bind(
+        &mut self,
+        name: &str,
+        t: KType,
+        annotations: &[Annotation],
+        typed: bool,
+    ) -> Result<(Place, bool), String> {
+        let module_only = || {
+            Err(format!(
+                "Las anotaciones solo se permiten en globales del módulo ('{}')",
+                name
+            ))
+        };
+        let annotated = annotations.iter().any(|a| a.name != ALLOW);
+        // Dentro de una función, `local g = ...` sin tipo también asigna la global visible
+        if let Some(global_t) = self.globals.get(name)
+            && (!self.in_function || !typed)
+            && self.local_scope(name).is_none()
+        {
+            if annotated {
+                return Err(format!("La global '{}' ya está declarada", name));
+            }
+            if !self.converts(global_t, &t) {
+                return Err(format!(
+                    "La global '{}' es de tipo {}, no se le puede asignar {}",
+                    name, global_t, t
+                ));
+            }
+            let place = Place::Global(name.to_string(), global_t.clone());
+            self.widen_after_assign(name, &t);
+            return Ok((place, false));
+        }
+        if self.in_function || self.depth > 0 {
+            if annotated {
+                return module_only();
+            }
+            let declared = match self.local_scope(name) {
+                Some(depth) if depth > 0 && !typed => {
+                    let local_t = self.declared_type(name).cloned().expect("local sin tipo");
+                    if !self.converts(&local_t, &t) {
+                        return Err(format!(
+                            "'{}' es de tipo {}, no se le puede asignar {} (declara otra con 'local {}: Tipo = ...')",
+                            name, local_t, t, name
+                        ));
+                    }
+                    let place = Place::Local(self.locals[name], local_t);
+                    self.widen_after_assign(name, &t);
+                    return Ok((place, false));
+                }
+                Some(depth) => {
+                    if depth > 0 {
+                        self.warn_shadowing(name);
+                    }
+                    self.declare_local(name, t.clone())
+                }
+                None => self.declare_local(name, t.clone()),
+            };
+            return Ok((Place::Local(declared, t), false));
+        }
+        self.check_annotations(annotations, "FIELD")?;
+        if t == KType::Void {
+            return Err(format!("La global '{}' no puede ser de tipo Void", name));
+        }
+        self.symbols.insert(name.to_string(), t.clone());
+        self.globals.insert(name.to_string(), t.clone());
+        Ok((Place::Global(name.to_string(), t), true))
+    }
+
+    pub fn check_expr(&self, expr: &Expr) -> Result<TExpr, String> {
+        match expr {
+            Expr::Number(n) => Ok(TExpr::new(TExprKind::Number(*n), KType::Int)),
+            Expr::String(s) => Ok(TExpr::new(TExprKind::String(s.clone()), KType::String)),
+            Expr::Boolean(b) => Ok(TExpr::new(TExprKind::Boolean(*b), KType::Bool)),
+            Expr::Identifier(n, _) => self.resolve(n).ok_or_else(|| self.undefined(n)),
+            Expr::Binary(l, op, r) => self.check_binary(l, op, r),
             Expr::ArrayLiteral(elems) => {
-                if elems.is_empty() {
-                    return Ok(KType::Array(Box::new(KType::Int)));
+                let Some(first) = elems.first() else {
+                    return Ok(TExpr::new(
+                        TExprKind::ArrayLiteral(Vec::new()),
+                        KType::Array(Box::new(KType::Int)),
+                    ));
+                };
+                let first = self.check_expr(first)?;
+                let elem_t = first.ktype.clone();
+                if elem_t == KType::Void {
+                    return Err("Un arreglo no puede contener Void".into());
                 }
-                Ok(KType::Array(Box::new(self.check_expr(&elems[0])?)))
+                let mut typed = vec![first];
+                for e in &elems[1..] {
+                    let value = self.check_expr(e)?;
+                    if !self.accepts(&elem_t, &value.ktype) {
+                        return Err(format!(
+                            "Los elementos de un arreglo deben ser del mismo tipo: se esperaba {}, se encontró {}",
+                            elem_t, value.ktype
+                        ));
+                    }
+                    typed.push(value.convert_to(&elem_t));
+                }
+                Ok(TExpr::new(
+                    TExprKind::ArrayLiteral(typed),
+                    KType::Array(Box::new(elem_t)),
+                ))
+            }
+            Expr::NewArray(elem_t, dims) => {
+                self.check_type(elem_t)?;
+                if *elem_t == KType::Void {
+                    return Err("No se puede crear un arreglo de Void".into());
+                }
+                let array_t = KType::Array(Box::new(elem_t.clone()));
+                // Array<Int[]>(n, m) reserva ambas dimensiones; Array<Int[]>(n) deja las filas en null
+                if dims.len() > array_t.array_depth() {
+                    return Err(format!(
+                        "Array<{}> recibe un tamaño por dimensión (como mucho {}), se pasaron {}",
+                        elem_t,
+                        array_t.array_depth(),
+                        dims.len()
+                    ));
+                }
+                let mut sizes = Vec::new();
+                for d in dims {
+                    let size = self.check_expr(d)?;
+                    if size.ktype != KType::Int {
+                        return Err(format!(
+                            "El tamaño de un arreglo debe ser Int, se encontró {}",
+                            size.ktype
+                        ));
+                    }
+                    sizes.push(size);
+                }
+                Ok(TExpr::new(TExprKind::NewArray(sizes), array_t))
+            }
+            Expr::Length(inner) => {
+                let inner = self.check_expr(inner)?;
+                match inner.ktype {
+                    KType::Array(_)
+                    | KType::String
+                    | KType::List(_)
+                    | KType::Set(_)
+                    | KType::Map(_, _) => {
+                        Ok(TExpr::new(TExprKind::Length(Box::new(inner)), KType::Int))
+                    }
+                    other => Err(format!(
+                        "len() requiere un arreglo, String o colección, se encontró {}",
+                        other
+                    )),
+                }
+            }
+            Expr::MapLiteral(entries) => {
+                let Some((k, v)) = entries.first() else {
+                    return Err(
+                        "No se puede inferir el tipo de un mapa vacío: declara su tipo (local m: Map<String, Int> = {})"
+                            .into(),
+                    );
+                };
+                let map_t = KType::Map(
+                    Box::new(self.check_expr(k)?.ktype),
+                    Box::new(self.check_expr(v)?.ktype),
+                );
+                self.check_value(expr, Some(&map_t))
             }
             Expr::ArrayAccess(name, idx) => {
-                self.check_expr(idx)?;
-                match self.symbols.get(name) {
-                    Some(KType::Array(inner)) => Ok(*inner.clone()),
-                    _ => Err(format!("'{}' no es un arreglo", name)),
+                let (container, index, elem_t) = self.check_index(name, idx)?;
+                Ok(TExpr::new(
+                    TExprKind::Index(Box::new(container), Box::new(index)),
+                    elem_t,
+                ))
+            }
+            Expr::Range(_, _, _) => {
+                Err("Un rango solo puede usarse en un 'for' o en un slice (nombre[a..b])".into())
+            }
+            Expr::Slice(name, range) => {
+                let Expr::Range(start, end, inclusive) = range.as_ref() else {
+                    unreachable!("el slice siempre contiene un rango");
+                };
+                let range = self.check_range(start, end, *inclusive)?;
+                match self.resolve(name) {
+                    Some(container)
+                        if matches!(container.ktype, KType::Array(_) | KType::String) =>
+                    {
+                        let t = container.ktype.clone();
+                        Ok(TExpr::new(
+                            TExprKind::Slice(Box::new(container), name.clone(), Box::new(range)),
+                            t,
+                        ))
+                    }
+                    Some(other) => Err(format!(
+                        "Solo se pueden recortar arreglos y String: '{}' es {}",
+                        name, other.ktype
+                    )),
+                    None => Err(self.undefined(name)),
+                }
+            }
+            Expr::Tuple(elems) => {
+                let mut typed = Vec::new();
+                for e in elems {
+                    let value = self.check_expr(e)?;
+                    if value.ktype == KType::Void {
+                        return Err("Una tupla no puede contener Void".into());
+                    }
+                    typed.push(value);
                 }
+                let types = typed.iter().map(|e| e.ktype.clone()).collect();
+                Ok(TExpr::new(TExprKind::Tuple(typed), KType::Tuple(types)))
+            }
+            Expr::Call(name, args) => self.check_call(name, args),
+            Expr::Cast(inner, target, safe) => {
+                let inner = self.check_expr(inner)?;
+                self.check_type(target)?;
+                self.check_cast(&inner.ktype, target, *safe)?;
+                Ok(TExpr::new(
+                    TExprKind::Cast(Box::new(inner), *safe),
+                    target.clone(),
+                ))
+            }
+            Expr::TypeTest(inner, target) => {
+                let inner = self.check_expr(inner)?;
+                self.check_type(target)?;
+                if !inner.ktype.is_reference() {
+                    return Err(format!(
+                        "'is' requiere una expresión de tipo referencia, se encontró {}",
+                        inner.ktype
+                    ));
+                }
+                let target_ref = Self::reference_view(target);
+                if !self.java.cast_possible(&inner.ktype, &target_ref) {
+                    return Err(format!("{} nunca puede ser {}", inner.ktype, target));
+                }
+                Ok(TExpr::new(
+                    TExprKind::TypeTest(Box::new(inner), target.clone()),
+                    KType::Bool,
+                ))
+            }
+            Expr::Input => Ok(TExpr::new(TExprKind::Input, KType::Int)),
+        }
+    }
+
+    // Aritmética con promoción numérica, `+` de String y comparaciones que dan Bool.
+    // Los operandos numéricos se convierten al tipo común
+    fn check_binary(&self, l: &Expr, op: &str, r: &Expr) -> Result<TExpr, String> {
+        let (l, r) = (self.check_expr(l)?, self.check_expr(r)?);
+        let (lt, rt) = (l.ktype.clone(), r.ktype.clone());
+        let mismatch = || format!("El operador '{}' no se puede aplicar a {} y {}", op, lt, rt);
+        if lt == KType::Void || rt == KType::Void {
+            return Err(mismatch());
+        }
+        let promoted = KType::promote(&lt, &rt);
+        let result_t = match op {
+            // Concatenación: basta con que un lado sea String; el otro se convierte a texto
+            "+" if lt == KType::String || rt == KType::String => {
+                return Ok(TExpr::new(
+                    TExprKind::Binary(Box::new(l), op.to_string(), Box::new(r)),
+                    KType::String,
+                ));
             }
-            _ => Ok(KType::Int),
+            "+" | "-" | "*" | "/" => promoted.clone().ok_or_else(mismatch)?,
+            "<" | ">" | "<=" | ">=" => promoted
+                .as_ref()
+                .map(|_| KType::Bool)
+                .ok_or_else(mismatch)?,
+            "==" | "!=" => {
+                // Números entre sí, Bool con Bool, y referencias que puedan ser el mismo objeto
+                let comparable = promoted.is_some()
+                    || (lt == KType::Bool && rt == KType::Bool)
+                    || (lt.is_reference()
+                        && rt.is_reference()
+                        && (self.java.cast_possible(&lt, &rt)
+                            || self.java.cast_possible(&rt, &lt)));
+                if !comparable {
+                    return Err(mismatch());
+                }
+                KType::Bool
+            }
+            _ => return Err(format!("Operador desconocido '{}'", op)),
+        };
+        let (l, r) = match &promoted {
+            Some(t) => (l.convert_to(t), r.convert_to(t)),
+            None => (l, r),
+        };
+        Ok(TExpr::new(
+            TExprKind::Binary(Box::new(l), op.to_string(), Box::new(r)),
+            result_t,
+        ))
+    }
+
+    // Como check_expr, pero un literal `[...]`/`{...}` toma el tipo de colección esperado
+    // y cualquier otro valor queda convertido al tipo declarado
+    fn check_value(&self, expr: &Expr, expected: Option<&KType>) -> Result<TExpr, String> {
+        let check_elements = |elems: &[&Expr], elem_t: &KType, what: &str| {
+            let mut typed = Vec::new();
+            for e in elems {
+                let value = self.check_expr(e)?;
+                if !self.accepts(elem_t, &value.ktype) {
+                    return Err(format!(
+                        "{} de tipo {} en un {}",
+                        what,
+                        value.ktype,
+                        expected.unwrap()
+                    ));
+                }
+                typed.push(value);
+            }
+            Ok(typed)
+        };
+        match (expected, expr) {
+            (Some(t @ (KType::List(elem_t) | KType::Set(elem_t))), Expr::ArrayLiteral(elems)) => {
+                let typed = check_elements(&elems.iter().collect::<Vec<_>>(), elem_t, "Elemento")?;
+                Ok(TExpr::new(TExprKind::ArrayLiteral(typed), t.clone()))
+            }
+            (Some(t @ KType::Map(key_t, value_t)), Expr::MapLiteral(entries)) => {
+                let keys = check_elements(
+                    &entries.iter().map(|(k, _)| k).collect::<Vec<_>>(),
+                    key_t,
+                    "Clave",
+                )?;
+                let values = check_elements(
+                    &entries.iter().map(|(_, v)| v).collect::<Vec<_>>(),
+                    value_t,
+                    "Valor",
+                )?;
+                Ok(TExpr::new(
+                    TExprKind::MapLiteral(keys.into_iter().zip(values).collect()),
+                    t.clone(),
+                ))
+            }
+            // `[]` o `[[1], [2]]` con el tipo declarado: cada elemento se comprueba contra el suyo
+            (Some(t @ KType::Array(elem_t)), Expr::ArrayLiteral(elems)) => {
+                let mut typed = Vec::new();
+                for e in elems {
+                    let value = self.check_value(e, Some(elem_t))?;
+                    if !self.converts(elem_t, &value.ktype) {
+                        return Err(format!("Elemento de tipo {} en un {}", value.ktype, t));
+                    }
+                    typed.push(value.convert_to(elem_t));
+                }
+                Ok(TExpr::new(TExprKind::ArrayLiteral(typed), t.clone()))
+            }
+            // Un literal entero que cabe en Byte/Short/Char se estrecha, como en Java
+            (Some(t @ (KType::Byte | KType::Short | KType::Char)), Expr::Number(n)) => {
+                let fits = match t {
+                    KType::Byte => i8::try_from(*n).is_ok(),
+                    KType::Short => i16::try_from(*n).is_ok(),
+                    _ => u16::try_from(*n).is_ok(),
+                };
+                if !fits {
+                    return Err(format!("El valor {} no cabe en {}", n, t));
+                }
+                Ok(TExpr::new(TExprKind::Number(*n), t.clone()))
+            }
+            // El tipo declarado manda: el valor debe poder convertirse a él
+            (Some(t), _) => {
+                let value = self.check_expr(expr)?;
+                if !self.converts(t, &value.ktype) {
+                    return Err(format!("Se esperaba {}, se encontró {}", t, value.ktype));
+                }
+                Ok(value.convert_to(t))
+            }
+            (None, _) => self.check_expr(expr),
+        }
+    }
+
+    // Asignable sin conversión: mismo tipo, o una referencia que es subclase del destino
+    fn accepts(&self, target: &KType, actual: &KType) -> bool {
+        if target == actual {
+            return true;
+        }
+        matches!(target, KType::Custom(_))
+            && actual.is_reference()
+            && self
+                .java
+                .is_subclass(&self.java.class_ref(actual), &self.java.class_ref(target))
+                == Some(true)
+    }
+
+    // Asignable como en una llamada Java: ensanchamiento, subtipos y boxing; los genéricos deben coincidir
+    fn converts(&self, target: &KType, actual: &KType) -> bool {
+        if target.is_erased() {
+            target == actual
+        } else {
+            self.java.arg_cost(target, actual).is_some()
+        }
+    }
+
+    // Un rango se recorre como una secuencia de Int
+    fn check_range(&self, start: &Expr, end: &Expr, inclusive: bool) -> Result<TExpr, String> {
+        let mut bounds = Vec::new();
+        for bound in [start, end] {
+            let value = self.check_expr(bound)?;
+            if value.ktype != KType::Int {
+                return Err(format!(
+                    "Los límites de un rango deben ser Int, se encontró {}",
+                    value.ktype
+                ));
+            }
+            bounds.push(Box::new(value));
+        }
+        let (end, start) = (bounds.pop().unwrap(), bounds.pop().unwrap());
+        Ok(TExpr::new(
+            TExprKind::Range(start, end, inclusive),
+            KType::Array(Box::new(KType::Int)),
+        ))
+    }
+
+    // `nombre[idx]`: arreglos y listas por Int, mapas por su tipo de clave.
+    // Devuelve el contenedor, el índice y el tipo del elemento
+    fn check_index(&self, name: &str, idx: &Expr) -> Result<(TExpr, TExpr, KType), String> {
+        let index = self.check_expr(idx)?;
+        let container = self.resolve(name).ok_or_else(|| self.undefined(name))?;
+        let (key_t, elem_t) = match &container.ktype {
+            KType::Array(inner) | KType::List(inner) => (KType::Int, (**inner).clone()),
+            KType::Map(k, v) => ((**k).clone(), (**v).clone()),
+            KType::Set(_) => return Err(format!("'{}' es un Set y no admite índices", name)),
+            _ => return Err(format!("'{}' no es un arreglo, lista ni mapa", name)),
+        };
+        if !self.accepts(&key_t, &index.ktype) {
+            return Err(format!(
+                "El índice de '{}' debe ser {}, se encontró {}",
+                name, key_t, index.ktype
+            ));
+        }
+        Ok((container, index, elem_t))
+    }
+
+    // Un primitivo se compara contra su clase envoltorio (Int -> java.lang.Integer)
+    fn reference_view(t: &KType) -> KType {
+        match t.box_class() {
+            Some(class) => KType::Custom(class.replace('/', ".")),
+            None => t.clone(),
+        }
+    }
+
+    fn check_cast(&self, from: &KType, to: &KType, safe: bool) -> Result<(), String> {
+        let impossible = || Err(format!("Conversión imposible de {} a {}", from, to));
+        if *from == KType::Void || *to == KType::Void {
+            return impossible();
+        }
+        if safe && !to.is_reference() {
+            return Err(format!(
+                "'as?' requiere un tipo referencia, se encontró {}",
+                to
+            ));
+        }
+        match (from.is_reference(), to.is_reference()) {
+            // Conversiones numéricas (i2l, d2i, ...); Bool solo a Bool
+            (false, false) => {
+                if from.is_numeric() == to.is_numeric() {
+                    Ok(())
+                } else {
+                    impossible()
+                }
+            }
+            // Boxing o unboxing: se comprueba contra la clase envoltorio
+            _ => {
+                if self
+                    .java
+                    .cast_possible(&Self::reference_view(from), &Self::reference_view(to))
+                {
+                    Ok(())
+                } else {
+                    impossible()
+                }
+            }
+        }
+    }
+
+    // Tras `if x is T then` (o `while x is T do`) `x` se trata como T dentro del cuerpo.
+    // Solo cambia su tipo estático: se sigue leyendo y escribiendo la misma local o
+    // global, con un checkcast en cada lectura. Las constantes no cambian
+    fn narrow(&mut self, cond: &Expr) -> Option<Narrowing> {
+        let Expr::TypeTest(inner, target) = cond else {
+            return None;
+        };
+        let Expr::Identifier(name, _) = inner.as_ref() else {
+            return None;
+        };
+        if self.constants.contains_key(name) {
+            return None;
+        }
+        let base = self.declared_type(name)?.clone();
+        let local = self.locals.get(name).copied();
+        Some(Narrowing {
+            name: name.clone(),
+            previous: self.symbols.insert(name.clone(), target.clone()),
+            previous_base: self.narrowed.insert(name.clone(), (local, base)),
+        })
+    }
+
+    fn restore_narrowing(&mut self, narrowing: Option<Narrowing>) {
+        let Some(narrowing) = narrowing else {
+            return;
+        };
+        match narrowing.previous_base {
+            Some(base) => self.narrowed.insert(narrowing.name.clone(), base),
+            None => self.narrowed.remove(&narrowing.name),
+        };
+        if let Some(previous) = narrowing.previous {
+            self.symbols.insert(narrowing.name, previous);
+        }
+    }
+
+    // Asignar a una variable estrechada un valor que no es del tipo estrechado
+    // devuelve la variable a su tipo declarado hasta el final del cuerpo
+    fn widen_after_assign(&mut self, name: &str, t: &KType) {
+        let Some(base) = self.declared_type(name).cloned() else {
+            return;
+        };
+        if let Some(current) = self.symbols.get(name)
+            && *current != base
+            && !self.accepts(current, t)
+        {
+            self.symbols.insert(name.to_string(), base);
         }
     }
 }
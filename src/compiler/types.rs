@@ -1,32 +1,166 @@
 // src/compiler/types.rs
+use std::fmt::{Display, Formatter};
+
 #[derive(Debug, Clone, PartialEq)]
 #[allow(dead_code)]
 pub enum KType {
     Int,
+    Long,
+    Float,
+    Double,
+    Byte,
+    Short,
+    Char,
     String,
     Bool,
     Void,
     Array(Box<KType>),
-    Custom(String), // Para clases de Java externas
+    List(Box<KType>),            // java.util.List<T>
+    Set(Box<KType>),             // java.util.Set<T>
+    Map(Box<KType>, Box<KType>), // java.util.Map<K, V>
+    Tuple(Vec<KType>),           // (Int, String): Object[] con los primitivos envueltos
+    Custom(String),              // Para clases de Java externas
 }
 
 impl KType {
     #[allow(dead_code)]
     pub fn is_reference(&self) -> bool {
+        matches!(
+            self,
+            KType::String
+                | KType::Array(_)
+                | KType::List(_)
+                | KType::Set(_)
+                | KType::Map(_, _)
+                | KType::Tuple(_)
+                | KType::Custom(_)
+        )
+    }
+
+    // Interfaz de java.util que representa al tipo y clase con la que se construyen los literales
+    pub fn collection_classes(&self) -> Option<(&'static str, &'static str)> {
+        match self {
+            KType::List(_) => Some(("java/util/List", "java/util/ArrayList")),
+            KType::Set(_) => Some(("java/util/Set", "java/util/LinkedHashSet")),
+            KType::Map(_, _) => Some(("java/util/Map", "java/util/LinkedHashMap")),
+            _ => None,
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            KType::Int
+                | KType::Long
+                | KType::Float
+                | KType::Double
+                | KType::Byte
+                | KType::Short
+                | KType::Char
+        )
+    }
+
+    // Promoción numérica binaria de Java: Double > Float > Long > Int (Byte/Short/Char suben a Int)
+    pub fn promote(a: &KType, b: &KType) -> Option<KType> {
+        if !a.is_numeric() || !b.is_numeric() {
+            return None;
+        }
+        Some(
+            [KType::Double, KType::Float, KType::Long]
+                .into_iter()
+                .find(|t| a == t || b == t)
+                .unwrap_or(KType::Int),
+        )
+    }
+
+    // Clase envoltorio usada al convertir un primitivo en referencia (y al revés)
+    pub fn box_class(&self) -> Option<&'static str> {
+        match self {
+            KType::Int => Some("java/lang/Integer"),
+            KType::Long => Some("java/lang/Long"),
+            KType::Float => Some("java/lang/Float"),
+            KType::Double => Some("java/lang/Double"),
+            KType::Byte => Some("java/lang/Byte"),
+            KType::Short => Some("java/lang/Short"),
+            KType::Char => Some("java/lang/Character"),
+            KType::Bool => Some("java/lang/Boolean"),
+            _ => None,
+        }
+    }
+
+    // El descriptor JVM no basta para conocer el tipo: genéricos y tuplas
+    pub fn is_erased(&self) -> bool {
+        matches!(
+            self,
+            KType::List(_) | KType::Set(_) | KType::Map(_, _) | KType::Tuple(_)
+        )
+    }
+
+    // Número de dimensiones: Int[][] -> 2, Int -> 0
+    pub fn array_depth(&self) -> usize {
         match self {
-            KType::String | KType::Array(_) | KType::Custom(_) => true,
-            _ => false,
+            KType::Array(inner) => 1 + inner.array_depth(),
+            _ => 0,
+        }
+    }
+
+    // Inverso aproximado de to_jvm_sig: las colecciones pierden sus argumentos genéricos
+    pub fn from_sig(sig: &str) -> KType {
+        match sig {
+            "I" => KType::Int,
+            "J" => KType::Long,
+            "F" => KType::Float,
+            "D" => KType::Double,
+            "B" => KType::Byte,
+            "S" => KType::Short,
+            "C" => KType::Char,
+            "Z" => KType::Bool,
+            "V" => KType::Void,
+            "Ljava/lang/String;" => KType::String,
+            s if s.starts_with('[') => KType::Array(Box::new(KType::from_sig(&s[1..]))),
+            s => KType::Custom(
+                s.trim_start_matches('L')
+                    .trim_end_matches(';')
+                    .replace('/', "."),
+            ),
         }
     }
 
+    // "(ILjava/lang/Object;)Ljava/lang/String;" -> ([Int, java.lang.Object], String)
+    pub fn from_method_sig(desc: &str) -> (Vec<KType>, KType) {
+        let close = desc.find(')').unwrap_or(desc.len() - 1);
+        let mut params = Vec::new();
+        let mut rest = &desc[1..close];
+        while !rest.is_empty() {
+            let dims = rest.len() - rest.trim_start_matches('[').len();
+            let end = match rest.as_bytes()[dims] {
+                b'L' => rest.find(';').map_or(rest.len(), |i| i + 1),
+                _ => dims + 1,
+            };
+            params.push(KType::from_sig(&rest[..end]));
+            rest = &rest[end..];
+        }
+        (params, KType::from_sig(&desc[close + 1..]))
+    }
+
     #[allow(dead_code)]
     pub fn to_jvm_sig(&self) -> String {
         match self {
             KType::Int => "I".into(),
+            KType::Long => "J".into(),
+            KType::Float => "F".into(),
+            KType::Double => "D".into(),
+            KType::Byte => "B".into(),
+            KType::Short => "S".into(),
+            KType::Char => "C".into(),
             KType::String => "Ljava/lang/String;".into(),
             KType::Bool => "Z".into(),
             KType::Void => "V".into(),
             KType::Array(t) => format!("[{}", t.to_jvm_sig()),
+            KType::List(_) => "Ljava/util/List;".into(),
+            KType::Set(_) => "Ljava/util/Set;".into(),
+            KType::Map(_, _) => "Ljava/util/Map;".into(),
+            KType::Tuple(_) => "[Ljava/lang/Object;".into(),
             KType::Custom(name) => {
                 // Convertimos java.util.ArrayList a java/util/ArrayList
                 let internal = name.replace('.', "/");
@@ -35,3 +169,21 @@ impl KType {
         }
     }
 }
+
+// Nombre del tipo tal como se escribe en Kujav, para los mensajes de error
+impl Display for KType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KType::Array(inner) => write!(f, "{inner}[]"),
+            KType::List(inner) => write!(f, "List<{inner}>"),
+            KType::Set(inner) => write!(f, "Set<{inner}>"),
+            KType::Map(k, v) => write!(f, "Map<{k}, {v}>"),
+            KType::Tuple(elems) => {
+                let names: Vec<String> = elems.iter().map(|e| e.to_string()).collect();
+                write!(f, "({})", names.join(", "))
+            }
+            KType::Custom(name) => write!(f, "{name}"),
+            other => write!(f, "{other:?}"),
+        }
+    }
+}
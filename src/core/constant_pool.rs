@@ -2,6 +2,7 @@
 
 pub enum Constant {
     Utf8(String),
+    Integer(i32),
     Class(u16),
     String(u16),
     FieldRef {
@@ -12,6 +13,10 @@ pub enum Constant {
         class_index: u16,
         name_and_type_index: u16,
     },
+    InterfaceMethodRef {
+        class_index: u16,
+        name_and_type_index: u16,
+    },
     NameAndType {
         name_index: u16,
         type_index: u16,
@@ -34,6 +39,11 @@ impl ConstantPool {
         self.entries.len() as u16
     }
 
+    pub fn add_integer(&mut self, value: i32) -> u16 {
+        self.entries.push(Constant::Integer(value));
+        self.entries.len() as u16
+    }
+
     pub fn add_class(&mut self, name_idx: u16) -> u16 {
         self.entries.push(Constant::Class(name_idx));
         self.entries.len() as u16
@@ -68,6 +78,41 @@ impl ConstantPool {
         self.entries.len() as u16
     }
 
+    pub fn add_interface_method_ref(&mut self, class_idx: u16, nt_idx: u16) -> u16 {
+        self.entries.push(Constant::InterfaceMethodRef {
+            class_index: class_idx,
+            name_and_type_index: nt_idx,
+        });
+        self.entries.len() as u16
+    }
+
+    /// Descriptor de un FieldRef, MethodRef o InterfaceMethodRef (índices desde 1).
+    pub fn member_descriptor(&self, idx: u16) -> Option<&str> {
+        let entry = |i: u16| self.entries.get((i as usize).checked_sub(1)?);
+        let nt = match entry(idx)? {
+            Constant::FieldRef {
+                name_and_type_index,
+                ..
+            }
+            | Constant::MethodRef {
+                name_and_type_index,
+                ..
+            }
+            | Constant::InterfaceMethodRef {
+                name_and_type_index,
+                ..
+            } => *name_and_type_index,
+            _ => return None,
+        };
+        let Constant::NameAndType { type_index, .. } = entry(nt)? else {
+            return None;
+        };
+        match entry(*type_index)? {
+            Constant::Utf8(desc) => Some(desc),
+            _ => None,
+        }
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
         bytes.extend_from_slice(&(self.entries.len() as u16 + 1).to_be_bytes());
@@ -78,6 +123,10 @@ impl ConstantPool {
                     bytes.extend_from_slice(&(s.len() as u16).to_be_bytes());
                     bytes.extend_from_slice(s.as_bytes());
                 }
+                Constant::Integer(v) => {
+                    bytes.push(3);
+                    bytes.extend_from_slice(&v.to_be_bytes());
+                }
                 Constant::Class(i) => {
                     bytes.push(7);
                     bytes.extend_from_slice(&i.to_be_bytes());
@@ -102,6 +151,14 @@ impl ConstantPool {
                     bytes.extend_from_slice(&class_index.to_be_bytes());
                     bytes.extend_from_slice(&name_and_type_index.to_be_bytes());
                 }
+                Constant::InterfaceMethodRef {
+                    class_index,
+                    name_and_type_index,
+                } => {
+                    bytes.push(11);
+                    bytes.extend_from_slice(&class_index.to_be_bytes());
+                    bytes.extend_from_slice(&name_and_type_index.to_be_bytes());
+                }
                 Constant::NameAndType {
                     name_index,
                     type_index,
@@ -4,9 +4,7 @@ pub type KujavResult<T> = Result<T, KujavError>;
 
 #[derive(Debug)]
 pub enum KujavError {
-    Syntax(String),
     Semantic(String),
-    Type(String),
     Dependency(String),
     Bytecode(String),
     Io(String),
@@ -14,15 +12,9 @@ pub enum KujavError {
 }
 
 impl KujavError {
-    pub fn syntax(msg: impl Into<String>) -> Self {
-        Self::Syntax(msg.into())
-    }
     pub fn semantic(msg: impl Into<String>) -> Self {
         Self::Semantic(msg.into())
     }
-    pub fn type_error(msg: impl Into<String>) -> Self {
-        Self::Type(msg.into())
-    }
     pub fn dependency(msg: impl Into<String>) -> Self {
         Self::Dependency(msg.into())
     }
@@ -37,6 +29,13 @@ impl KujavError {
     }
 }
 
+// Aviso que no detiene la compilación; su lint decide si se muestra o se trata como error
+#[derive(Debug)]
+pub struct KujavWarning {
+    pub lint: &'static str,
+    pub message: String,
+}
+
 impl From<std::io::Error> for KujavError {
     fn from(value: std::io::Error) -> Self {
         Self::Io(value.to_string())
@@ -46,9 +45,7 @@ impl From<std::io::Error> for KujavError {
 impl Display for KujavError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            KujavError::Syntax(m) => write!(f, "Error[SyntaxError]:\n{m}"),
             KujavError::Semantic(m) => write!(f, "Error[SemanticError]:\n{m}"),
-            KujavError::Type(m) => write!(f, "Error[TypeError]:\n{m}"),
             KujavError::Dependency(m) => write!(f, "Error[DependencyError]:\n{m}"),
             KujavError::Bytecode(m) => write!(f, "Error[BytecodeError]:\n{m}"),
             KujavError::Io(m) => write!(f, "Error[IoError]:\n{m}"),
@@ -56,3 +53,9 @@ impl Display for KujavError {
         }
     }
 }
+
+impl Display for KujavWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "warning[{}]: {}", self.lint, self.message)
+    }
+}
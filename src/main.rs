@@ -10,10 +10,12 @@ mod toml_config;
 use std::fs;
 use std::path::Path;
 
-use cli::{Cli, Commands};
+use cli::{BuildOptions, Cli, Commands};
+use compiler::cfg::CfgContext;
+use compiler::lints::LintLevels;
 use errors::{KujavError, KujavResult};
 use package::lockfile::write_lockfile;
-use package::resolver::validate_java_classpath;
+use package::resolver::{build_class_index, validate_java_classpath};
 use toml_config::KujavToml;
 
 fn main() {
@@ -27,9 +29,9 @@ fn run() -> KujavResult<()> {
     let cli = Cli::parse()?;
     match cli.command {
         Commands::New { project } => new_project(&project),
-        Commands::Build => build_project(),
-        Commands::Run => run_project(),
-        Commands::Check => check_project(),
+        Commands::Build(options) => build_project(&options),
+        Commands::Run(options) => run_project(&options),
+        Commands::Check(options) => check_project(&options),
         Commands::Install => {
             println!("kujav install: dependency fetch pipeline pending registry integration");
             Ok(())
@@ -68,43 +70,75 @@ fn load_project() -> KujavResult<(KujavToml, String)> {
     Ok((cfg, source))
 }
 
-fn check_project() -> KujavResult<()> {
-    let (_cfg, source) = load_project()?;
-    compiler::pipeline::check_only(&source)?;
+// Features y perfil activos para los @cfg del código
+fn cfg_context(cfg: &KujavToml, options: &BuildOptions) -> KujavResult<CfgContext> {
+    if !cfg.profiles.contains(&options.profile) {
+        return Err(KujavError::toml(format!(
+            "unknown profile '{}' (declare it as [profile.{}])",
+            options.profile, options.profile
+        )));
+    }
+    Ok(CfgContext {
+        features: cfg.resolve_features(&options.features, options.default_features)?,
+        known_features: cfg.features.keys().cloned().collect(),
+        profile: options.profile.clone(),
+        known_profiles: cfg.profiles.clone(),
+    })
+}
+
+fn check_project(options: &BuildOptions) -> KujavResult<()> {
+    let (cfg, source) = load_project()?;
+    let cfg_ctx = cfg_context(&cfg, options)?;
+    validate_java_classpath(&cfg)?;
+    let lints = LintLevels::from_config(&cfg.lints)?;
+    let classes = build_class_index(&cfg)?;
+    compiler::pipeline::check_only(&source, classes, &cfg_ctx, &lints)?;
     println!("check finished without errors");
     Ok(())
 }
 
-fn build_project() -> KujavResult<()> {
+fn build_project(options: &BuildOptions) -> KujavResult<()> {
     let (cfg, source) = load_project()?;
+    let cfg_ctx = cfg_context(&cfg, options)?;
+    let lints = LintLevels::from_config(&cfg.lints)?;
     fs::create_dir_all("target")?;
     write_lockfile(&cfg)?;
     validate_java_classpath(&cfg)?;
+    let classes = build_class_index(&cfg)?;
 
-    let class_path = format!("target/{}.class", cfg.package.name);
-    compiler::pipeline::compile_to_class(&cfg.package.name, &source, &class_path)?;
+    let class_name = compiler::pipeline::compile_to_class(
+        &cfg.package.name,
+        &source,
+        "target",
+        classes,
+        &cfg_ctx,
+        &lints,
+    )?;
 
     let jar_path = format!("target/{}.jar", cfg.package.name);
-    compiler::pipeline::package_jar(&cfg, &class_path, &jar_path)?;
+    compiler::pipeline::package_jar(&cfg, "target", &class_name, &jar_path)?;
     println!("Built {}", jar_path);
     Ok(())
 }
 
-fn run_project() -> KujavResult<()> {
+fn run_project(options: &BuildOptions) -> KujavResult<()> {
     let cfg = KujavToml::from_path("kujav.toml")?;
     let jar_path = format!("target/{}.jar", cfg.package.name);
-    if !Path::new(&jar_path).exists() {
-        build_project()?;
+    // Con features o perfil explícitos el JAR anterior puede no corresponder: se recompila
+    let explicit =
+        !options.features.is_empty() || !options.default_features || options.profile != "debug";
+    if explicit || !Path::new(&jar_path).exists() {
+        build_project(options)?;
     }
     let status = std::process::Command::new("java")
         .arg("-jar")
         .arg(&jar_path)
+        .args(&options.program_args)
         .status()
         .map_err(|_| KujavError::io("failed to execute java runtime"))?;
+    // El código de salida del programa (lo que devuelve `main`) es el de kujav run
     if !status.success() {
-        return Err(KujavError::bytecode(
-            "java runtime returned non-zero status",
-        ));
+        std::process::exit(status.code().unwrap_or(1));
     }
     Ok(())
 }
@@ -1,6 +1,9 @@
 use std::path::Path;
 
 use crate::errors::{KujavError, KujavResult};
+use crate::reader::class_index::ClassIndex;
+use crate::reader::jar_reader::index_jar;
+use crate::reader::jdk_reader::JdkInstall;
 use crate::toml_config::KujavToml;
 
 pub fn validate_java_classpath(cfg: &KujavToml) -> KujavResult<()> {
@@ -13,3 +16,26 @@ pub fn validate_java_classpath(cfg: &KujavToml) -> KujavResult<()> {
     }
     Ok(())
 }
+
+/// Builds the symbol index from `[java].classpath` jars plus the JDK platform classes.
+/// Without a JDK the index only holds the classpath jars, so `java.*` references
+/// are checked as unknown classes.
+pub fn build_class_index(cfg: &KujavToml) -> KujavResult<ClassIndex> {
+    let mut index = ClassIndex::new();
+    for dep in &cfg.java.classpath {
+        index_jar(dep, &mut index).map_err(KujavError::dependency)?;
+    }
+
+    let jdk = match JdkInstall::locate() {
+        Ok(jdk) => jdk,
+        Err(e) => {
+            eprintln!("warning: {e}; indexing only the [java].classpath jars");
+            return Ok(index);
+        }
+    };
+    let platform = jdk
+        .load_platform_classes(cfg.java.release)
+        .map_err(KujavError::dependency)?;
+    index.extend(platform);
+    Ok(index)
+}
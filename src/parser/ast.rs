@@ -1,27 +1,123 @@
 // src/parser/ast.rs
 use crate::compiler::types::KType;
+use std::fmt::{Display, Formatter};
+
+// Posición en el archivo .kj (línea y columna, desde 1) para los mensajes de error
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct Span {
+    pub line: usize,
+    pub col: usize,
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.col)
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum Expr {
     Number(i32),
     String(String),
     Boolean(bool),
-    Identifier(String),
+    Identifier(String, Span),
     Binary(Box<Expr>, String, Box<Expr>),
-    Call(String, Vec<Expr>),
+    Call(String, Vec<Arg>), // (Función Kujav o Clase.método estático de Java, Args)
     Input,
-    ArrayLiteral(Vec<Expr>),
+    ArrayLiteral(Vec<Expr>), // Arreglo, o List/Set si el tipo declarado lo pide
+    MapLiteral(Vec<(Expr, Expr)>), // { clave: valor, ... }
+    Tuple(Vec<Expr>),        // (a, b) o `return a, b`
     ArrayAccess(String, Box<Expr>),
+    NewArray(KType, Vec<Expr>), // Array<T>(n, ...): (Tipo del elemento, Tamaño de cada dimensión)
+    Length(Box<Expr>),          // len(x) o x.length
+    Range(Box<Expr>, Box<Expr>, bool), // (Inicio, Fin, incluye el fin: `..` frente a `..<`)
+    Slice(String, Box<Expr>),   // nombre[a..b] (la Expr es un Range)
+    Cast(Box<Expr>, KType, bool), // (Expr, Tipo destino, es `as?`)
+    TypeTest(Box<Expr>, KType), // expr is Tipo
+}
+
+impl Expr {
+    // Identificador sin posición, para las consultas internas del compilador
+    pub fn var(name: &str) -> Expr {
+        Expr::Identifier(name.to_string(), Span::default())
+    }
 }
 
+// @Nombre(clave = valor, ...); un argumento sin clave se guarda como "value"
+#[derive(Debug, Clone)]
+pub struct Annotation {
+    pub name: String,
+    pub args: Vec<(String, AnnotationValue)>,
+}
+
+#[derive(Debug, Clone)]
+pub enum AnnotationValue {
+    Number(i32),
+    String(String),
+    Boolean(bool),
+    Name(String), // Constante de enum (EventPriority.HIGH)
+    Annotation(Annotation),
+    Array(Vec<AnnotationValue>),
+}
+
+// Modificador de una declaración del nivel superior; sin modificador es pública
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Visibility {
+    Pub,
+    Private,
+    Internal, // Visible en el paquete (package-private en la JVM)
+}
+
+// Condición de `@cfg(...)`, evaluada contra las features y el perfil del build
+#[derive(Debug, Clone)]
+pub enum CfgPredicate {
+    Feature(String), // feature = "paper"
+    Profile(String), // debug, release o un [profile.<nombre>] de kujav.toml
+    Not(Box<CfgPredicate>),
+    All(Vec<CfgPredicate>),
+    Any(Vec<CfgPredicate>),
+}
+
+pub type Param = (String, KType, Vec<Annotation>, Option<Expr>, bool); // (Nombre, Tipo, Anotaciones, Valor por defecto, `...` variádico)
+pub type Arg = (Option<String>, Expr); // (Nombre si se pasa como `nombre = valor`, Valor)
+
 #[derive(Debug, Clone)]
 pub enum Stmt {
-    Let(String, Expr, Option<String>), // (Nombre, Expr, Tipo Opcional)
+    Package(String),              // com.org.plugin
+    Cfg(CfgPredicate, Box<Stmt>), // Declaración o sentencia condicional; no llega al análisis semántico
+    Import(String),               // Nombre completo: org.bukkit.entity.Player u org.bukkit.*
+    Let(
+        String,
+        Expr,
+        Option<KType>,
+        Vec<Annotation>,
+        Option<Visibility>,
+        Span,
+    ), // (Nombre, Expr, Tipo Opcional, Anotaciones, Visibilidad, Posición del nombre)
+    LetMulti(Vec<String>, Expr, Span), // local a, b = f()  (la Expr es una tupla)
+    Const(
+        String,
+        Expr,
+        Option<KType>,
+        Vec<Annotation>,
+        Option<Visibility>,
+    ), // Solo a nivel de módulo
     Print(Expr),
     If(Expr, Vec<Stmt>, Option<Vec<Stmt>>),
     While(Expr, Vec<Stmt>),
-    Function(String, Vec<(String, KType)>, Vec<Stmt>, KType), // (Nombre, Params, Cuerpo, Retorno)
-    Call(String, Vec<Expr>),
+    For(String, Expr, Vec<Stmt>, Span), // (Variable, Colección o arreglo, Cuerpo, Posición de la variable)
+    Function(
+        String,
+        Vec<Param>,
+        Vec<Stmt>,
+        KType,
+        Vec<Annotation>,
+        Option<String>,
+        Option<Visibility>,
+    ), // (Nombre, Params, Cuerpo, Retorno, Anotaciones, Documentación `---`, Visibilidad)
+    Call(String, Vec<Arg>),
     Return(Option<Expr>),
     IndexAssign(String, Expr, Expr),
+    Asm(String), // Texto del bloque `asm ... end`, una instrucción JVM por línea
+    Assert(Expr, Option<Expr>), // (Condición, Mensaje)
 }
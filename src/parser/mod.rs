@@ -1,6 +1,6 @@
 // src/parser/mod.rs
 pub mod ast;
-use self::ast::{Expr, Stmt};
+use self::ast::{Annotation, AnnotationValue, Arg, CfgPredicate, Expr, Span, Stmt, Visibility};
 use crate::compiler::types::KType;
 use pest::Parser;
 use pest_derive::Parser as PestParser;
@@ -18,10 +18,16 @@ pub fn parse_to_ast(input: &str) -> Vec<Stmt> {
     let mut statements = Vec::new();
     for pair in pairs.into_inner() {
         match pair.as_rule() {
+            Rule::package_decl => {
+                let name = pair.into_inner().next().unwrap().as_str().to_string();
+                statements.push(Stmt::Package(name));
+            }
             Rule::import_decl => {
-                // Por ahora los imports se pueden manejar como una sentencia especial o metadatos
+                let path = pair.into_inner().next().unwrap().as_str().replace('"', "");
+                statements.push(Stmt::Import(path));
             }
-            Rule::declaration | Rule::statement => {
+            Rule::const_decl => statements.push(process_const(pair)),
+            Rule::declaration | Rule::statement | Rule::cfg_item => {
                 if let Some(stmt) = process_stmt(pair) {
                     statements.push(stmt);
                 }
@@ -32,25 +38,148 @@ pub fn parse_to_ast(input: &str) -> Vec<Stmt> {
     statements
 }
 
+fn process_const(pair: pest::iterators::Pair<Rule>) -> Stmt {
+    let mut inner = pair.into_inner().peekable();
+    let mut annotations = Vec::new();
+    while let Some(a) = inner.next_if(|a| a.as_rule() == Rule::annotation) {
+        annotations.push(parse_annotation(a));
+    }
+    let visibility = inner
+        .next_if(|v| v.as_rule() == Rule::visibility)
+        .map(parse_visibility);
+    let name = inner.next().unwrap().as_str().to_string();
+    let mut next = inner.next().unwrap();
+    let mut const_type = None;
+    if next.as_rule() == Rule::type_name {
+        const_type = Some(parse_type(next));
+        next = inner.next().unwrap();
+    }
+    Stmt::Const(
+        name,
+        process_expr(next),
+        const_type,
+        annotations,
+        visibility,
+    )
+}
+
+// Varios @cfg seguidos deben cumplirse todos; los `---` de delante documentan la función
+fn process_cfg_item(pair: pest::iterators::Pair<Rule>) -> Option<Stmt> {
+    let mut predicates = Vec::new();
+    let mut doc_lines = Vec::new();
+    let mut item = None;
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            Rule::doc_comment => doc_lines.push(doc_text(p.as_str())),
+            Rule::cfg_attr => predicates.push(parse_cfg_pred(p.into_inner().nth(1).unwrap())),
+            Rule::const_decl => item = Some(process_const(p)),
+            _ => item = process_stmt(p),
+        }
+    }
+    if let Some(Stmt::Function(.., doc, _)) = &mut item
+        && !doc_lines.is_empty()
+    {
+        // Los `---` entre los @cfg y la función van detrás de los de delante
+        doc_lines.extend(doc.take());
+        *doc = Some(doc_lines.join("\n"));
+    }
+    let predicate = if predicates.len() == 1 {
+        predicates.pop().unwrap()
+    } else {
+        CfgPredicate::All(predicates)
+    };
+    Some(Stmt::Cfg(predicate, Box::new(item?)))
+}
+
+fn parse_cfg_pred(pair: pest::iterators::Pair<Rule>) -> CfgPredicate {
+    let inner = pair.into_inner().next().unwrap();
+    match inner.as_rule() {
+        Rule::cfg_feature => {
+            let name = inner.into_inner().next().unwrap().as_str().replace('"', "");
+            CfgPredicate::Feature(name)
+        }
+        Rule::cfg_not => {
+            CfgPredicate::Not(Box::new(parse_cfg_pred(inner.into_inner().next().unwrap())))
+        }
+        Rule::cfg_list => {
+            let mut parts = inner.into_inner();
+            let all = parts.next().unwrap().as_str() == "all";
+            let args = parts.map(parse_cfg_pred).collect();
+            if all {
+                CfgPredicate::All(args)
+            } else {
+                CfgPredicate::Any(args)
+            }
+        }
+        _ => CfgPredicate::Profile(inner.as_str().to_string()),
+    }
+}
+
 fn process_stmt(pair: pest::iterators::Pair<Rule>) -> Option<Stmt> {
+    if pair.as_rule() == Rule::cfg_item {
+        return process_cfg_item(pair);
+    }
     let inner_pair = pair.into_inner().next().unwrap();
     match inner_pair.as_rule() {
         Rule::let_decl => {
-            let mut inner = inner_pair.into_inner();
-            let name = inner.next().unwrap().as_str().to_string();
+            let mut inner = inner_pair.into_inner().peekable();
+            let mut annotations = Vec::new();
+            while let Some(a) = inner.next_if(|a| a.as_rule() == Rule::annotation) {
+                annotations.push(parse_annotation(a));
+            }
+            let visibility = inner
+                .next_if(|v| v.as_rule() == Rule::visibility)
+                .map(parse_visibility);
+            let name_pair = inner.next().unwrap();
+            let (name, span) = (name_pair.as_str().to_string(), span_of(&name_pair));
             let mut next = inner.next().unwrap();
-            let mut type_name_str = None;
+            let mut let_type = None;
 
             if next.as_rule() == Rule::type_name {
-                // Guardamos el nombre del tipo como String para el AST
-                type_name_str = Some(next.as_str().to_string());
+                let_type = Some(parse_type(next));
                 next = inner.next().unwrap();
             }
             let expr = process_expr(next);
-            Some(Stmt::Let(name, expr, type_name_str))
+            Some(Stmt::Let(
+                name,
+                expr,
+                let_type,
+                annotations,
+                visibility,
+                span,
+            ))
+        }
+        Rule::let_multi => {
+            let span = span_of(&inner_pair);
+            let mut names = Vec::new();
+            let mut values = Vec::new();
+            for p in inner_pair.into_inner() {
+                match p.as_rule() {
+                    Rule::identifier => names.push(p.as_str().to_string()),
+                    _ => values.push(process_expr(p)),
+                }
+            }
+            let value = if values.len() == 1 {
+                values.pop().unwrap()
+            } else {
+                Expr::Tuple(values)
+            };
+            Some(Stmt::LetMulti(names, value, span))
         }
         Rule::fun_decl => {
-            let mut inner = inner_pair.into_inner();
+            let mut inner = inner_pair.into_inner().peekable();
+            let mut doc_lines = Vec::new();
+            while let Some(d) = inner.next_if(|d| d.as_rule() == Rule::doc_comment) {
+                doc_lines.push(doc_text(d.as_str()));
+            }
+            let doc = (!doc_lines.is_empty()).then(|| doc_lines.join("\n"));
+            let mut annotations = Vec::new();
+            while let Some(a) = inner.next_if(|a| a.as_rule() == Rule::annotation) {
+                annotations.push(parse_annotation(a));
+            }
+            let visibility = inner
+                .next_if(|v| v.as_rule() == Rule::visibility)
+                .map(parse_visibility);
             let name = inner.next().unwrap().as_str().to_string();
             let mut params = Vec::new();
             let mut ret_type = KType::Void;
@@ -60,10 +189,36 @@ fn process_stmt(pair: pest::iterators::Pair<Rule>) -> Option<Stmt> {
                 match next.as_rule() {
                     Rule::parameter_list => {
                         for p in next.into_inner() {
-                            let mut p_inner = p.into_inner();
+                            if p.as_rule() == Rule::self_param {
+                                // El tipo de `self` es el receptor; sin receptor queda Void y lo rechaza el análisis
+                                let receiver = name
+                                    .rsplit_once('.')
+                                    .map_or(KType::Void, |(r, _)| parse_base_type(r));
+                                params.push((
+                                    "self".to_string(),
+                                    receiver,
+                                    Vec::new(),
+                                    None,
+                                    false,
+                                ));
+                                continue;
+                            }
+                            let mut p_annotations = Vec::new();
+                            let mut p_inner = p.into_inner().peekable();
+                            while let Some(a) = p_inner.next_if(|a| a.as_rule() == Rule::annotation)
+                            {
+                                p_annotations.push(parse_annotation(a));
+                            }
+                            // `...args: T` llega a la función como un arreglo T[]
+                            let variadic =
+                                p_inner.next_if(|v| v.as_rule() == Rule::variadic).is_some();
                             let p_name = p_inner.next().unwrap().as_str().to_string();
-                            let p_type = parse_type(p_inner.next().unwrap());
-                            params.push((p_name, p_type));
+                            let mut p_type = parse_type(p_inner.next().unwrap());
+                            if variadic {
+                                p_type = KType::Array(Box::new(p_type));
+                            }
+                            let default = p_inner.next().map(process_expr);
+                            params.push((p_name, p_type, p_annotations, default, variadic));
                         }
                     }
                     Rule::type_name => {
@@ -79,7 +234,15 @@ fn process_stmt(pair: pest::iterators::Pair<Rule>) -> Option<Stmt> {
                     _ => {}
                 }
             }
-            Some(Stmt::Function(name, params, body, ret_type))
+            Some(Stmt::Function(
+                name,
+                params,
+                body,
+                ret_type,
+                annotations,
+                doc,
+                visibility,
+            ))
         }
         Rule::if_stmt => {
             let mut inner = inner_pair.into_inner();
@@ -115,8 +278,39 @@ fn process_stmt(pair: pest::iterators::Pair<Rule>) -> Option<Stmt> {
             }
             Some(Stmt::While(cond, body))
         }
+        Rule::for_stmt => {
+            let mut inner = inner_pair.into_inner();
+            let var_pair = inner.next().unwrap();
+            let (var, span) = (var_pair.as_str().to_string(), span_of(&var_pair));
+            let iterable = match inner.nth(1).unwrap() {
+                // Saltamos `in`
+                r if r.as_rule() == Rule::range => process_range(r),
+                e => process_expr(e),
+            };
+            let body = inner
+                .next()
+                .unwrap()
+                .into_inner()
+                .filter_map(process_stmt)
+                .collect();
+            Some(Stmt::For(var, iterable, body, span))
+        }
+        Rule::asm_stmt => {
+            let body = inner_pair.into_inner().next().unwrap().as_str().to_string();
+            Some(Stmt::Asm(body))
+        }
+        Rule::assert_stmt => {
+            let mut inner = inner_pair.into_inner().skip(1); // assert_kw
+            let cond = process_expr(inner.next().unwrap());
+            Some(Stmt::Assert(cond, inner.next().map(process_expr)))
+        }
         Rule::return_stmt => {
-            let expr = inner_pair.into_inner().next().map(process_expr);
+            let mut values: Vec<Expr> = inner_pair.into_inner().map(process_expr).collect();
+            let expr = match values.len() {
+                0 => None,
+                1 => values.pop(),
+                _ => Some(Expr::Tuple(values)),
+            };
             Some(Stmt::Return(expr))
         }
         Rule::print_stmt => {
@@ -126,33 +320,143 @@ fn process_stmt(pair: pest::iterators::Pair<Rule>) -> Option<Stmt> {
         Rule::call_stmt => {
             let mut inner = inner_pair.into_inner();
             let name = inner.next().unwrap().as_str().to_string();
-            let mut args = Vec::new();
-            if let Some(arg_list) = inner.next() {
-                for arg in arg_list.into_inner() {
-                    args.push(process_expr(arg));
-                }
-            }
+            let args = inner.next().map(process_args).unwrap_or_default();
             Some(Stmt::Call(name, args))
         }
+        Rule::index_assign => {
+            let mut inner = inner_pair.into_inner();
+            let name = inner.next().unwrap().as_str().to_string();
+            let idx = process_expr(inner.next().unwrap());
+            let val = process_expr(inner.next().unwrap());
+            Some(Stmt::IndexAssign(name, idx, val))
+        }
         _ => None,
     }
 }
 
-// Función auxiliar para parsear tipos (ej: Int, String, Int[])
-fn parse_type(pair: pest::iterators::Pair<Rule>) -> KType {
+fn span_of(pair: &pest::iterators::Pair<Rule>) -> Span {
+    let (line, col) = pair.as_span().start_pos().line_col();
+    Span { line, col }
+}
+
+fn parse_visibility(pair: pest::iterators::Pair<Rule>) -> Visibility {
+    match pair.as_str() {
+        "private" => Visibility::Private,
+        "internal" => Visibility::Internal,
+        _ => Visibility::Pub,
+    }
+}
+
+// "--- Cura al jugador" -> "Cura al jugador"
+fn doc_text(line: &str) -> String {
+    let text = line
+        .trim_end_matches('\r')
+        .strip_prefix("---")
+        .unwrap_or(line);
+    text.strip_prefix(' ').unwrap_or(text).to_string()
+}
+
+fn process_args(list: pest::iterators::Pair<Rule>) -> Vec<Arg> {
+    list.into_inner()
+        .map(|arg| {
+            let mut a_inner = arg.into_inner();
+            let first = a_inner.next().unwrap();
+            match a_inner.next() {
+                Some(value) => (Some(first.as_str().to_string()), process_expr(value)),
+                None => (None, process_expr(first)),
+            }
+        })
+        .collect()
+}
+
+fn parse_annotation(pair: pest::iterators::Pair<Rule>) -> Annotation {
     let mut inner = pair.into_inner();
-    let base_name = inner.next().unwrap().as_str();
-    let mut ktype = match base_name {
+    let name = inner.next().unwrap().as_str().to_string();
+    let mut args = Vec::new();
+    if let Some(arg_list) = inner.next() {
+        for arg in arg_list.into_inner() {
+            let mut a_inner = arg.into_inner();
+            let first = a_inner.next().unwrap();
+            let (key, value) = match a_inner.next() {
+                Some(value) => (first.as_str().to_string(), value),
+                None => ("value".to_string(), first),
+            };
+            args.push((key, parse_annotation_value(value)));
+        }
+    }
+    Annotation { name, args }
+}
+
+fn parse_annotation_value(pair: pest::iterators::Pair<Rule>) -> AnnotationValue {
+    let inner = pair.into_inner().next().unwrap();
+    match inner.as_rule() {
+        Rule::annotation => AnnotationValue::Annotation(parse_annotation(inner)),
+        Rule::annotation_array => {
+            AnnotationValue::Array(inner.into_inner().map(parse_annotation_value).collect())
+        }
+        Rule::string => AnnotationValue::String(inner.as_str().replace("\"", "")),
+        Rule::number => AnnotationValue::Number(inner.as_str().parse().unwrap()),
+        _ => match inner.as_str() {
+            "true" => AnnotationValue::Boolean(true),
+            "false" => AnnotationValue::Boolean(false),
+            name => AnnotationValue::Name(name.to_string()),
+        },
+    }
+}
+
+// Función auxiliar para parsear tipos (ej: Int, String, Int[], Map<String, Int>)
+fn parse_type(pair: pest::iterators::Pair<Rule>) -> KType {
+    let start = pair.as_span().start();
+    let text = pair.as_str();
+    let mut inner = pair.into_inner().peekable();
+    let first = inner.next().unwrap();
+    if first.as_rule() == Rule::tuple_type {
+        return KType::Tuple(first.into_inner().map(parse_type).collect());
+    }
+    let base_name = first.as_str();
+    let mut name_end = first.as_span().end();
+    let args: Vec<KType> = inner
+        .next_if(|p| p.as_rule() == Rule::type_args)
+        .map(|a| {
+            name_end = a.as_span().end();
+            a.into_inner().map(parse_type).collect()
+        })
+        .unwrap_or_default();
+    // Los [] que siguen a los argumentos genéricos: List<Int[]> no es un arreglo
+    let dimensions = inner.filter(|p| p.as_rule() == Rule::array_suffix).count();
+    let mut args = args.into_iter();
+    let mut ktype = match (base_name, args.len()) {
+        ("List", 1) => KType::List(Box::new(args.next().unwrap())),
+        ("Set", 1) => KType::Set(Box::new(args.next().unwrap())),
+        ("Map", 2) => KType::Map(
+            Box::new(args.next().unwrap()),
+            Box::new(args.next().unwrap()),
+        ),
+        // Número de argumentos incorrecto: el análisis semántico lo rechaza como tipo desconocido
+        ("List" | "Set" | "Map", _) => KType::Custom(text[..name_end - start].to_string()),
+        _ => parse_base_type(base_name),
+    };
+    for _ in 0..dimensions {
+        ktype = KType::Array(Box::new(ktype));
+    }
+    ktype
+}
+
+// Los argumentos genéricos de otras clases Java se descartan (borrado de tipos)
+fn parse_base_type(base_name: &str) -> KType {
+    match base_name {
         "Int" => KType::Int,
+        "Long" => KType::Long,
+        "Float" => KType::Float,
+        "Double" => KType::Double,
+        "Byte" => KType::Byte,
+        "Short" => KType::Short,
+        "Char" => KType::Char,
         "String" => KType::String,
         "Bool" => KType::Bool,
+        "Any" => KType::Custom("java.lang.Object".to_string()),
         _ => KType::Custom(base_name.to_string()),
-    };
-    // Manejar dimensiones de arreglos []
-    for _ in inner {
-        ktype = KType::Array(Box::new(ktype));
     }
-    ktype
 }
 
 // (Las funciones process_expr, process_term, etc. se mantienen igual pero apuntando a las reglas de la nueva gramática)
@@ -160,6 +464,11 @@ fn process_expr(pair: pest::iterators::Pair<Rule>) -> Expr {
     let mut inner = pair.into_inner();
     let mut expr = process_term(inner.next().unwrap());
     while let Some(op_pair) = inner.next() {
+        if op_pair.as_rule() == Rule::type_test {
+            let t = parse_type(op_pair.into_inner().last().unwrap());
+            expr = Expr::TypeTest(Box::new(expr), t);
+            continue;
+        }
         let op = op_pair.as_str().to_string();
         let right = process_term(inner.next().unwrap());
         expr = Expr::Binary(Box::new(expr), op, Box::new(right));
@@ -178,23 +487,77 @@ fn process_term(pair: pest::iterators::Pair<Rule>) -> Expr {
     expr
 }
 
+fn process_range(pair: pest::iterators::Pair<Rule>) -> Expr {
+    let mut inner = pair.into_inner();
+    let start = process_term(inner.next().unwrap());
+    let inclusive = inner.next().unwrap().as_str() == "..";
+    let end = process_term(inner.next().unwrap());
+    Expr::Range(Box::new(start), Box::new(end), inclusive)
+}
+
 fn process_factor(pair: pest::iterators::Pair<Rule>) -> Expr {
     let mut inner = pair.into_inner();
-    let mut expr = process_primary_expr(inner.next().unwrap());
+    let mut expr = process_cast(inner.next().unwrap());
     while let Some(op_pair) = inner.next() {
         let op = op_pair.as_str().to_string();
-        let right = process_primary_expr(inner.next().unwrap());
+        let right = process_cast(inner.next().unwrap());
         expr = Expr::Binary(Box::new(expr), op, Box::new(right));
     }
     expr
 }
 
+// primary seguido de cero o más `as Tipo` / `as? Tipo`
+fn process_cast(pair: pest::iterators::Pair<Rule>) -> Expr {
+    let mut inner = pair.into_inner();
+    let mut expr = process_primary_expr(inner.next().unwrap());
+    for suffix in inner {
+        let mut s_inner = suffix.into_inner();
+        let safe = s_inner.next().unwrap().as_str() == "as?";
+        let t = parse_type(s_inner.next().unwrap());
+        expr = Expr::Cast(Box::new(expr), t, safe);
+    }
+    expr
+}
+
 fn process_primary_expr(pair: pest::iterators::Pair<Rule>) -> Expr {
     let inner = pair.into_inner().next().expect("Primary vacío");
     match inner.as_rule() {
         Rule::string => Expr::String(inner.as_str().replace("\"", "")),
         Rule::number => Expr::Number(inner.as_str().parse().unwrap()),
-        Rule::identifier => Expr::Identifier(inner.as_str().to_string()),
+        Rule::boolean => Expr::Boolean(inner.as_str() == "true"),
+        Rule::input_kw => Expr::Input,
+        Rule::identifier => Expr::Identifier(inner.as_str().to_string(), span_of(&inner)),
+        Rule::call_expr => {
+            let mut c_inner = inner.into_inner();
+            let name = c_inner.next().unwrap().as_str().to_string();
+            let args = c_inner.next().map(process_args).unwrap_or_default();
+            match (name.as_str(), args.as_slice()) {
+                ("len", [(None, value)]) => Expr::Length(Box::new(value.clone())),
+                _ => Expr::Call(name, args),
+            }
+        }
+        Rule::array_new => {
+            let mut n_inner = inner.into_inner();
+            let elem = parse_type(n_inner.next().unwrap());
+            Expr::NewArray(elem, n_inner.map(process_expr).collect())
+        }
+        Rule::length_access => {
+            let name = inner.into_inner().next().unwrap();
+            Expr::Length(Box::new(Expr::Identifier(
+                name.as_str().to_string(),
+                span_of(&name),
+            )))
+        }
+        Rule::slice => {
+            let mut s_inner = inner.into_inner();
+            let name = s_inner.next().unwrap().as_str().to_string();
+            Expr::Slice(name, Box::new(process_range(s_inner.next().unwrap())))
+        }
+        Rule::array_access => {
+            let mut a_inner = inner.into_inner();
+            let name = a_inner.next().unwrap().as_str().to_string();
+            Expr::ArrayAccess(name, Box::new(process_expr(a_inner.next().unwrap())))
+        }
         Rule::array_lit => {
             let mut elements = Vec::new();
             for e in inner.into_inner() {
@@ -202,7 +565,77 @@ fn process_primary_expr(pair: pest::iterators::Pair<Rule>) -> Expr {
             }
             Expr::ArrayLiteral(elements)
         }
+        Rule::tuple_lit => Expr::Tuple(inner.into_inner().map(process_expr).collect()),
+        Rule::map_lit => Expr::MapLiteral(
+            inner
+                .into_inner()
+                .map(|entry| {
+                    let mut kv = entry.into_inner();
+                    (
+                        process_expr(kv.next().unwrap()),
+                        process_expr(kv.next().unwrap()),
+                    )
+                })
+                .collect(),
+        ),
         Rule::expression => process_expr(inner), // Para ( expr )
         _ => unreachable!("Error en primary: {:?}", inner.as_rule()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(stmt: &Stmt) -> Option<&str> {
+        match stmt {
+            Stmt::Function(.., doc, _) => doc.as_deref(),
+            Stmt::Cfg(_, item) => doc(item),
+            other => panic!("se esperaba una función: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn doc_comment_before_a_function_is_kept() {
+        let ast = parse_to_ast("--- Suma.\n--- Dos líneas.\n@Deprecated\nfunction f()\nend\n");
+        assert_eq!(doc(&ast[0]), Some("Suma.\nDos líneas."));
+    }
+
+    #[test]
+    fn doc_comment_before_a_cfg_function_is_kept() {
+        let ast = parse_to_ast("--- Solo en debug.\n@cfg(debug)\npub function f()\nend\n");
+        assert!(matches!(ast[0], Stmt::Cfg(..)));
+        assert_eq!(doc(&ast[0]), Some("Solo en debug."));
+    }
+
+    #[test]
+    fn triple_dash_elsewhere_is_an_ordinary_comment() {
+        let source = "\
+--- suelto
+local m = {
+    --- en un mapa
+    \"a\": 1
+}
+local l = [
+    1,
+    --- en una lista
+    2
+]
+function f(
+    a: Int,
+    --- en los parámetros
+    b: Int
+): Int
+    return a +
+        --- en una expresión
+        b
+end
+--- antes de un @cfg que no es una función
+@cfg(debug)
+local x = 1
+";
+        let ast = parse_to_ast(source);
+        assert_eq!(ast.len(), 4);
+        assert_eq!(doc(&ast[2]), None);
+    }
+}
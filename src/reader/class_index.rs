@@ -0,0 +1,308 @@
+// src/reader/class_index.rs
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use cafebabe::attributes::{AnnotationElementValue, AttributeData, AttributeInfo};
+
+pub const ACC_PUBLIC: u16 = 0x0001;
+pub const ACC_PRIVATE: u16 = 0x0002;
+pub const ACC_STATIC: u16 = 0x0008;
+pub const ACC_FINAL: u16 = 0x0010;
+pub const ACC_VARARGS: u16 = 0x0080;
+pub const ACC_INTERFACE: u16 = 0x0200;
+pub const ACC_ABSTRACT: u16 = 0x0400;
+pub const ACC_ANNOTATION: u16 = 0x2000;
+pub const ACC_ENUM: u16 = 0x4000;
+
+#[derive(Debug, Clone)]
+pub struct MemberInfo {
+    pub name: String,
+    pub descriptor: String,
+    pub access_flags: u16,
+    pub has_default: bool, // Elemento de anotación con AnnotationDefault
+}
+
+// @Retention y @Target de un tipo anotación (nombres de las constantes del enum)
+#[derive(Debug, Clone)]
+pub struct AnnotationMeta {
+    pub retention: String,
+    pub targets: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClassInfo {
+    pub name: String, // Nombre interno: java/util/ArrayList
+    pub super_name: Option<String>,
+    pub interfaces: Vec<String>,
+    pub access_flags: u16,
+    pub fields: Vec<MemberInfo>,
+    pub methods: Vec<MemberInfo>,
+    pub annotation: Option<AnnotationMeta>,
+}
+
+impl ClassInfo {
+    pub fn is_annotation(&self) -> bool {
+        self.access_flags & ACC_ANNOTATION != 0
+    }
+
+    /// Elementos de un tipo anotación: sus métodos abstractos de instancia
+    /// (`<clinit>` y los métodos estáticos de la interfaz no cuentan).
+    pub fn annotation_elements(&self) -> impl Iterator<Item = &MemberInfo> {
+        self.methods
+            .iter()
+            .filter(|m| m.access_flags & (ACC_ABSTRACT | ACC_STATIC) == ACC_ABSTRACT)
+    }
+
+    pub fn field(&self, name: &str) -> Option<&MemberInfo> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+}
+
+/// Índice de símbolos Java visibles para el compilador (classpath + plataforma JDK).
+#[derive(Debug, Default)]
+pub struct ClassIndex {
+    classes: HashMap<String, ClassInfo>,
+}
+
+impl ClassIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+
+    /// Las clases del classpath tienen prioridad: no se sobreescribe una entrada existente.
+    pub fn insert(&mut self, class: ClassInfo) {
+        self.classes.entry(class.name.clone()).or_insert(class);
+    }
+
+    pub fn extend(&mut self, other: ClassIndex) {
+        for (_, class) in other.classes {
+            self.insert(class);
+        }
+    }
+
+    /// Acepta nombres internos (`java/util/List`) o con puntos (`java.util.List`).
+    pub fn get(&self, name: &str) -> Option<&ClassInfo> {
+        self.classes.get(&name.replace('.', "/"))
+    }
+
+    pub fn has_package(&self, package: &str) -> bool {
+        let prefix = format!("{package}/");
+        self.classes.keys().any(|k| {
+            k.strip_prefix(&prefix)
+                .is_some_and(|rest| !rest.contains('/'))
+        })
+    }
+
+    /// Convierte un `cafebabe::ClassFile` en una entrada del índice, omitiendo miembros privados.
+    pub fn class_from_bytes(bytes: &[u8]) -> Result<ClassInfo, String> {
+        let class = cafebabe::parse_class(bytes)?;
+        let fields = class
+            .fields
+            .iter()
+            .filter(|f| f.access_flags.bits() & ACC_PRIVATE == 0)
+            .map(|f| MemberInfo {
+                name: f.name.to_string(),
+                descriptor: f.descriptor.to_string(),
+                access_flags: f.access_flags.bits(),
+                has_default: false,
+            })
+            .collect();
+        let methods = class
+            .methods
+            .iter()
+            .filter(|m| m.access_flags.bits() & ACC_PRIVATE == 0)
+            .map(|m| MemberInfo {
+                name: m.name.to_string(),
+                descriptor: m.descriptor.to_string(),
+                access_flags: m.access_flags.bits(),
+                has_default: m
+                    .attributes
+                    .iter()
+                    .any(|a| matches!(a.data, AttributeData::AnnotationDefault(_))),
+            })
+            .collect();
+        let annotation = (class.access_flags.bits() & ACC_ANNOTATION != 0)
+            .then(|| annotation_meta(&class.attributes));
+        Ok(ClassInfo {
+            name: class.this_class.to_string(),
+            super_name: class.super_class.as_ref().map(|s| s.to_string()),
+            interfaces: class.interfaces.iter().map(|i| i.to_string()).collect(),
+            access_flags: class.access_flags.bits(),
+            fields,
+            methods,
+            annotation,
+        })
+    }
+
+    /// Formato de caché en texto plano, una línea por clase o miembro:
+    /// `C <flags> <nombre> <super|-> <interfaces separadas por comas|->`
+    /// `A <retención> <targets separados por comas|->` (solo tipos anotación)
+    /// `F <flags> <nombre> <descriptor>` / `M <flags> <nombre> <descriptor> [D]`
+    /// y una línea final `E <número de clases>` que detecta cachés truncadas.
+    pub fn to_cache_string(&self) -> String {
+        let mut names: Vec<&String> = self.classes.keys().collect();
+        names.sort();
+
+        let mut out = String::new();
+        for name in names {
+            let class = &self.classes[name];
+            let interfaces = if class.interfaces.is_empty() {
+                "-".to_string()
+            } else {
+                class.interfaces.join(",")
+            };
+            writeln!(
+                &mut out,
+                "C {} {} {} {}",
+                class.access_flags,
+                class.name,
+                class.super_name.as_deref().unwrap_or("-"),
+                interfaces
+            )
+            .expect("write cache class line");
+            if let Some(meta) = &class.annotation {
+                let targets = if meta.targets.is_empty() {
+                    "-".to_string()
+                } else {
+                    meta.targets.join(",")
+                };
+                writeln!(&mut out, "A {} {}", meta.retention, targets)
+                    .expect("write cache annotation line");
+            }
+            for f in &class.fields {
+                writeln!(&mut out, "F {} {} {}", f.access_flags, f.name, f.descriptor)
+                    .expect("write cache field line");
+            }
+            for m in &class.methods {
+                let default = if m.has_default { " D" } else { "" };
+                writeln!(
+                    &mut out,
+                    "M {} {} {}{}",
+                    m.access_flags, m.name, m.descriptor, default
+                )
+                .expect("write cache method line");
+            }
+        }
+        writeln!(&mut out, "E {}", self.classes.len()).expect("write cache trailer line");
+        out
+    }
+
+    pub fn from_cache_string(content: &str) -> Result<Self, String> {
+        let mut index = ClassIndex::new();
+        let mut current: Option<ClassInfo> = None;
+        let mut expected: Option<usize> = None;
+
+        for (n, line) in content.lines().enumerate() {
+            let parts: Vec<&str> = line.split(' ').collect();
+            let bad_line = || format!("línea {} de caché inválida: {}", n + 1, line);
+            if expected.is_some() {
+                return Err(bad_line());
+            }
+            if parts[0] == "E" && parts.len() == 2 {
+                expected = Some(parts[1].parse().map_err(|_| bad_line())?);
+                continue;
+            }
+            if parts[0] == "A" && parts.len() == 3 {
+                let class = current.as_mut().ok_or_else(bad_line)?;
+                class.annotation = Some(AnnotationMeta {
+                    retention: parts[1].to_string(),
+                    targets: if parts[2] == "-" {
+                        Vec::new()
+                    } else {
+                        parts[2].split(',').map(str::to_string).collect()
+                    },
+                });
+                continue;
+            }
+            let flags: u16 = parts
+                .get(1)
+                .and_then(|f| f.parse().ok())
+                .ok_or_else(bad_line)?;
+            match (parts.first().copied(), parts.len()) {
+                (Some("C"), 5) => {
+                    if let Some(done) = current.take() {
+                        index.insert(done);
+                    }
+                    current = Some(ClassInfo {
+                        name: parts[2].to_string(),
+                        super_name: (parts[3] != "-").then(|| parts[3].to_string()),
+                        interfaces: if parts[4] == "-" {
+                            Vec::new()
+                        } else {
+                            parts[4].split(',').map(str::to_string).collect()
+                        },
+                        access_flags: flags,
+                        fields: Vec::new(),
+                        methods: Vec::new(),
+                        annotation: None,
+                    });
+                }
+                (Some(kind @ ("F" | "M")), 4 | 5) => {
+                    let class = current.as_mut().ok_or_else(bad_line)?;
+                    let member = MemberInfo {
+                        name: parts[2].to_string(),
+                        descriptor: parts[3].to_string(),
+                        access_flags: flags,
+                        has_default: parts.get(4) == Some(&"D"),
+                    };
+                    if kind == "F" {
+                        class.fields.push(member);
+                    } else {
+                        class.methods.push(member);
+                    }
+                }
+                _ => return Err(bad_line()),
+            }
+        }
+        if let Some(done) = current.take() {
+            index.insert(done);
+        }
+        match expected {
+            Some(count) if count == index.classes.len() => Ok(index),
+            Some(count) => Err(format!(
+                "caché inválida: se esperaban {count} clases y hay {}",
+                index.classes.len()
+            )),
+            None => Err("caché truncada: falta la línea final".to_string()),
+        }
+    }
+}
+
+// Sin @Retention la JVM asume CLASS; sin @Target la anotación vale en cualquier declaración
+fn annotation_meta(attributes: &[AttributeInfo]) -> AnnotationMeta {
+    let mut meta = AnnotationMeta {
+        retention: "CLASS".to_string(),
+        targets: Vec::new(),
+    };
+    for attr in attributes {
+        let AttributeData::RuntimeVisibleAnnotations(annotations) = &attr.data else {
+            continue;
+        };
+        for ann in annotations {
+            for element in &ann.elements {
+                match (ann.type_descriptor.as_ref(), &element.value) {
+                    (
+                        "Ljava/lang/annotation/Retention;",
+                        AnnotationElementValue::EnumConstant { const_name, .. },
+                    ) => meta.retention = const_name.to_string(),
+                    (
+                        "Ljava/lang/annotation/Target;",
+                        AnnotationElementValue::ArrayValue(values),
+                    ) => {
+                        for v in values {
+                            if let AnnotationElementValue::EnumConstant { const_name, .. } = v {
+                                meta.targets.push(const_name.to_string());
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    meta
+}
@@ -1,7 +1,9 @@
-#![allow(dead_code)]
 use std::fs::File;
+use std::io::Read;
 use zip::ZipArchive;
 
+use crate::reader::class_index::ClassIndex;
+
 pub struct JarMetadata {
     pub class_names: Vec<String>,
 }
@@ -19,3 +21,24 @@ pub fn read_jar(path: &str) -> Result<JarMetadata, String> {
     }
     Ok(JarMetadata { class_names })
 }
+
+pub fn index_jar(path: &str, index: &mut ClassIndex) -> Result<(), String> {
+    let file = File::open(path).map_err(|e| format!("{path}: {e}"))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("{path}: {e}"))?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| format!("{path}: {e}"))?;
+        let name = entry.name().to_string();
+        if !name.ends_with(".class") || name.ends_with("module-info.class") {
+            continue;
+        }
+        let mut bytes = Vec::with_capacity(entry.size() as usize);
+        entry
+            .read_to_end(&mut bytes)
+            .map_err(|e| format!("{path}!{name}: {e}"))?;
+        let class =
+            ClassIndex::class_from_bytes(&bytes).map_err(|e| format!("{path}!{name}: {e}"))?;
+        index.insert(class);
+    }
+    Ok(())
+}
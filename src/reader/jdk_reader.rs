@@ -0,0 +1,448 @@
+// src/reader/jdk_reader.rs
+//
+// Lectura de las clases de plataforma de la JDK: la imagen `lib/modules` (jimage)
+// para la versión instalada, o `lib/ct.sym` cuando se pide una vista `--release N`.
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use zip::ZipArchive;
+
+use crate::reader::class_index::{ACC_PUBLIC, ClassIndex};
+
+// Se incrementa cuando cambia el formato de `ClassIndex::to_cache_string`
+const CACHE_FORMAT: u32 = 3;
+
+const JIMAGE_MAGIC: u32 = 0xCAFE_DADA;
+const JIMAGE_HEADER_SIZE: usize = 7 * 4;
+
+// Atributos de una "location" dentro del jimage (ver ImageLocation.java en la JDK).
+const ATTRIBUTE_END: u8 = 0;
+const ATTRIBUTE_MODULE: u8 = 1;
+const ATTRIBUTE_PARENT: u8 = 2;
+const ATTRIBUTE_BASE: u8 = 3;
+const ATTRIBUTE_EXTENSION: u8 = 4;
+const ATTRIBUTE_OFFSET: u8 = 5;
+const ATTRIBUTE_COMPRESSED: u8 = 6;
+const ATTRIBUTE_UNCOMPRESSED: u8 = 7;
+const ATTRIBUTE_COUNT: usize = 8;
+
+#[derive(Debug)]
+pub struct JdkInstall {
+    pub home: PathBuf,
+    pub version: String,
+    pub feature: u32,
+}
+
+impl JdkInstall {
+    /// Busca la JDK en `JAVA_HOME` y, si no existe, a partir del `java` del `PATH`.
+    pub fn locate() -> Result<Self, String> {
+        let home = std::env::var_os("JAVA_HOME")
+            .map(PathBuf::from)
+            .filter(|p| p.join("release").exists())
+            .or_else(java_home_from_path)
+            .ok_or("no se encontró una JDK: define JAVA_HOME o añade `java` al PATH")?;
+
+        let release = fs::read_to_string(home.join("release"))
+            .map_err(|e| format!("{}: {e}", home.join("release").display()))?;
+        let version = release
+            .lines()
+            .find_map(|l| l.strip_prefix("JAVA_VERSION="))
+            .map(|v| v.trim_matches('"').to_string())
+            .ok_or_else(|| format!("{}: falta JAVA_VERSION", home.join("release").display()))?;
+        Ok(Self {
+            feature: feature_version(&version)?,
+            home,
+            version,
+        })
+    }
+
+    /// Carga las clases de plataforma, usando la caché en disco si ya existe.
+    pub fn load_platform_classes(&self, release: Option<u32>) -> Result<ClassIndex, String> {
+        let release = release.filter(|r| *r != self.feature);
+        let cache_key = match release {
+            Some(r) => format!("jdk-{}-release-{r}.v{CACHE_FORMAT}.idx", self.version),
+            None => format!("jdk-{}.v{CACHE_FORMAT}.idx", self.version),
+        };
+        let cache_path = cache_dir().map(|d| d.join(cache_key));
+
+        if let Some(path) = &cache_path
+            && let Ok(content) = fs::read_to_string(path)
+            && let Ok(index) = ClassIndex::from_cache_string(&content)
+        {
+            return Ok(index);
+        }
+
+        let index = match release {
+            Some(r) if r > self.feature => {
+                return Err(format!(
+                    "--release {r} no está disponible en la JDK {} instalada",
+                    self.version
+                ));
+            }
+            Some(r) => read_ct_sym(&self.home.join("lib/ct.sym"), r)?,
+            None => read_jimage(&self.home.join("lib/modules"))?,
+        };
+
+        // La caché es una optimización: si no se puede escribir seguimos sin ella.
+        if let Some(path) = &cache_path {
+            let _ = write_cache(path, &index.to_cache_string());
+        }
+        Ok(index)
+    }
+}
+
+// Se escribe en un temporal del mismo directorio y se renombra, así otro proceso
+// nunca lee una caché a medio escribir.
+fn write_cache(path: &Path, content: &str) -> std::io::Result<()> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(dir)?;
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+fn java_home_from_path() -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join("java"))
+        .find(|candidate| candidate.is_file())
+        .and_then(|java| fs::canonicalize(java).ok())
+        .and_then(|java| java.parent()?.parent().map(Path::to_path_buf))
+        .filter(|home| home.join("release").exists())
+}
+
+fn cache_dir() -> Option<PathBuf> {
+    if let Some(home) = std::env::var_os("KUJAV_HOME") {
+        return Some(PathBuf::from(home).join("cache"));
+    }
+    std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".kujav").join("cache"))
+}
+
+// "1.8.0_392" -> 8, "17.0.15" -> 17, "21" -> 21
+fn feature_version(version: &str) -> Result<u32, String> {
+    let mut parts = version.split(['.', '_', '-', '+']);
+    let first = parts.next().unwrap_or_default();
+    let feature = if first == "1" {
+        parts.next().unwrap_or_default()
+    } else {
+        first
+    };
+    feature
+        .parse()
+        .map_err(|_| format!("versión de JDK no reconocida: {version}"))
+}
+
+// En ct.sym los directorios raíz enumeran las releases que comparten una firma:
+// "7", "8", "9" y luego "A" = 10, "B" = 11, ...
+fn release_char(release: u32) -> Option<char> {
+    match release {
+        7..=9 => char::from_digit(release, 10),
+        10..=35 => char::from_u32('A' as u32 + release - 10),
+        _ => None,
+    }
+}
+
+pub fn read_ct_sym(path: &Path, release: u32) -> Result<ClassIndex, String> {
+    let tag = release_char(release).ok_or_else(|| format!("--release {release} inválida"))?;
+    let file = File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("{}: {e}", path.display()))?;
+    let mut index = ClassIndex::new();
+
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        let name = entry.name().to_string();
+        // <releases>/<módulo>/<paquete>/<Clase>.sig
+        let Some((releases, rest)) = name.split_once('/') else {
+            continue;
+        };
+        if !releases.contains(tag) || !rest.ends_with(".sig") || rest.ends_with("module-info.sig") {
+            continue;
+        }
+        let mut bytes = Vec::with_capacity(entry.size() as usize);
+        entry
+            .read_to_end(&mut bytes)
+            .map_err(|e| format!("ct.sym!{name}: {e}"))?;
+        let class =
+            ClassIndex::class_from_bytes(&bytes).map_err(|e| format!("ct.sym!{name}: {e}"))?;
+        if class.access_flags & ACC_PUBLIC != 0 {
+            index.insert(class);
+        }
+    }
+
+    if index.is_empty() {
+        return Err(format!(
+            "{} no contiene firmas para --release {release}",
+            path.display()
+        ));
+    }
+    Ok(index)
+}
+
+struct JImage {
+    data: Vec<u8>,
+    big_endian: bool,
+    table_length: usize,
+    locations_size: usize,
+    strings_size: usize,
+}
+
+impl JImage {
+    fn open(path: &Path) -> Result<Self, String> {
+        let data = fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        if data.len() < JIMAGE_HEADER_SIZE {
+            return Err(format!("{}: jimage truncado", path.display()));
+        }
+        let magic = [data[0], data[1], data[2], data[3]];
+        let big_endian = if u32::from_le_bytes(magic) == JIMAGE_MAGIC {
+            false
+        } else if u32::from_be_bytes(magic) == JIMAGE_MAGIC {
+            true
+        } else {
+            return Err(format!(
+                "{}: no es un jimage (magic inválido)",
+                path.display()
+            ));
+        };
+
+        let mut image = Self {
+            data,
+            big_endian,
+            table_length: 0,
+            locations_size: 0,
+            strings_size: 0,
+        };
+        // Header: magic, versión, flags, resourceCount, tableLength, locationsSize, stringsSize
+        image.table_length = image.u4(16)? as usize;
+        image.locations_size = image.u4(20)? as usize;
+        image.strings_size = image.u4(24)? as usize;
+        if image.index_size() > image.data.len() {
+            return Err(format!("{}: índice de jimage truncado", path.display()));
+        }
+        Ok(image)
+    }
+
+    fn u4(&self, pos: usize) -> Result<u32, String> {
+        let b: [u8; 4] = self
+            .data
+            .get(pos..pos + 4)
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| format!("lectura fuera del jimage en el offset {pos}"))?;
+        Ok(if self.big_endian {
+            u32::from_be_bytes(b)
+        } else {
+            u32::from_le_bytes(b)
+        })
+    }
+
+    fn offsets_start(&self) -> usize {
+        JIMAGE_HEADER_SIZE + self.table_length * 4
+    }
+
+    fn locations_start(&self) -> usize {
+        self.offsets_start() + self.table_length * 4
+    }
+
+    fn strings_start(&self) -> usize {
+        self.locations_start() + self.locations_size
+    }
+
+    fn index_size(&self) -> usize {
+        self.strings_start() + self.strings_size
+    }
+
+    fn string(&self, offset: u64) -> Result<&str, String> {
+        let start = self.strings_start().saturating_add(offset as usize);
+        let bytes = self
+            .data
+            .get(start..self.index_size())
+            .ok_or_else(|| format!("string fuera del jimage en el offset {offset}"))?;
+        let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+        std::str::from_utf8(&bytes[..end])
+            .map_err(|_| format!("string no UTF-8 en el offset {offset}"))
+    }
+
+    // Cada atributo es un byte `kind << 3 | (len - 1)` seguido de `len` bytes big-endian.
+    fn location(&self, offset: usize) -> Result<[u64; ATTRIBUTE_COUNT], String> {
+        let mut attrs = [0u64; ATTRIBUTE_COUNT];
+        let mut pos = self.locations_start() + offset;
+        let truncated = || format!("location truncada en el offset {offset}");
+        while pos < self.strings_start() {
+            let byte = *self.data.get(pos).ok_or_else(truncated)?;
+            let kind = byte >> 3;
+            if kind == ATTRIBUTE_END || kind as usize >= ATTRIBUTE_COUNT {
+                break;
+            }
+            let len = (byte & 0x7) as usize + 1;
+            let value = self
+                .data
+                .get(pos + 1..pos + 1 + len)
+                .ok_or_else(truncated)?
+                .iter()
+                .fold(0u64, |acc, b| (acc << 8) | *b as u64);
+            attrs[kind as usize] = value;
+            pos += 1 + len;
+        }
+        Ok(attrs)
+    }
+}
+
+pub fn read_jimage(path: &Path) -> Result<ClassIndex, String> {
+    let image = JImage::open(path)?;
+    let mut index = ClassIndex::new();
+    let corrupt = |e: String| format!("{}: {e}", path.display());
+
+    for i in 0..image.table_length {
+        let offset = image.u4(image.offsets_start() + i * 4).map_err(corrupt)?;
+        let attrs = image.location(offset as usize).map_err(corrupt)?;
+        let string = |kind: u8| image.string(attrs[kind as usize]).map_err(corrupt);
+        if string(ATTRIBUTE_EXTENSION)? != "class" {
+            continue;
+        }
+        let base = string(ATTRIBUTE_BASE)?;
+        if base == "module-info" {
+            continue;
+        }
+        let name = format!(
+            "/{}/{}/{}.class",
+            string(ATTRIBUTE_MODULE)?,
+            string(ATTRIBUTE_PARENT)?,
+            base
+        );
+        if attrs[ATTRIBUTE_COMPRESSED as usize] != 0 {
+            return Err(format!(
+                "{}: recurso comprimido no soportado ({name}); usa una JDK sin `jlink --compress`",
+                path.display()
+            ));
+        }
+
+        let start = image
+            .index_size()
+            .saturating_add(attrs[ATTRIBUTE_OFFSET as usize] as usize);
+        let end = start.saturating_add(attrs[ATTRIBUTE_UNCOMPRESSED as usize] as usize);
+        let bytes = image
+            .data
+            .get(start..end)
+            .ok_or_else(|| format!("{}: recurso fuera de rango ({name})", path.display()))?;
+        let class =
+            ClassIndex::class_from_bytes(bytes).map_err(|e| format!("jimage!{name}: {e}"))?;
+        if class.access_flags & ACC_PUBLIC != 0 {
+            index.insert(class);
+        }
+    }
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feature_version_accepts_old_and_new_schemes() {
+        assert_eq!(feature_version("1.8.0_392"), Ok(8));
+        assert_eq!(feature_version("17.0.15"), Ok(17));
+        assert_eq!(feature_version("21"), Ok(21));
+        assert_eq!(feature_version("22-ea"), Ok(22));
+        assert!(feature_version("jdk").is_err());
+    }
+
+    #[test]
+    fn release_char_follows_ct_sym_directories() {
+        assert_eq!(release_char(8), Some('8'));
+        assert_eq!(release_char(9), Some('9'));
+        assert_eq!(release_char(10), Some('A'));
+        assert_eq!(release_char(17), Some('H'));
+        assert_eq!(release_char(6), None);
+        assert_eq!(release_char(36), None);
+    }
+
+    // public class p/A extends java/lang/Object, sin miembros
+    fn class_bytes() -> Vec<u8> {
+        let mut b = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 49, 0, 5];
+        for (i, name) in ["p/A", "java/lang/Object"].iter().enumerate() {
+            b.push(1);
+            b.extend_from_slice(&(name.len() as u16).to_be_bytes());
+            b.extend_from_slice(name.as_bytes());
+            b.push(7);
+            b.extend_from_slice(&(i as u16 * 2 + 1).to_be_bytes());
+        }
+        b.extend_from_slice(&[0x00, 0x21, 0, 2, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0]);
+        b
+    }
+
+    // Un jimage little-endian con una sola location: /java.base/p/A.class
+    fn jimage_bytes(resource: &[u8]) -> Vec<u8> {
+        let strings = b"\0java.base\0p\0A\0class\0";
+        let mut location = Vec::new();
+        for (kind, value) in [
+            (ATTRIBUTE_MODULE, 1),
+            (ATTRIBUTE_PARENT, 11),
+            (ATTRIBUTE_BASE, 13),
+            (ATTRIBUTE_EXTENSION, 15),
+            (ATTRIBUTE_OFFSET, 0),
+            (ATTRIBUTE_UNCOMPRESSED, resource.len() as u8),
+        ] {
+            location.extend_from_slice(&[kind << 3, value]);
+        }
+        location.push(ATTRIBUTE_END);
+
+        let header = [
+            JIMAGE_MAGIC,
+            1 << 16,
+            0,
+            1,
+            1,
+            location.len() as u32,
+            strings.len() as u32,
+        ];
+        let mut data: Vec<u8> = header.iter().flat_map(|v| v.to_le_bytes()).collect();
+        data.extend_from_slice(&0u32.to_le_bytes()); // redirect
+        data.extend_from_slice(&0u32.to_le_bytes()); // offset de la location
+        data.extend_from_slice(&location);
+        data.extend_from_slice(strings);
+        data.extend_from_slice(resource);
+        data
+    }
+
+    fn write_temp(name: &str, data: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("kujav-{}-{name}", std::process::id()));
+        fs::write(&path, data).unwrap();
+        path
+    }
+
+    #[test]
+    fn reads_classes_from_a_jimage() {
+        let path = write_temp("modules", &jimage_bytes(&class_bytes()));
+        let index = read_jimage(&path);
+        let _ = fs::remove_file(&path);
+        let index = index.unwrap();
+        let class = index.get("p.A").expect("p/A indexada");
+        assert_eq!(class.super_name.as_deref(), Some("java/lang/Object"));
+    }
+
+    #[test]
+    fn corrupt_jimage_is_an_error() {
+        let mut data = jimage_bytes(&class_bytes());
+        // La extensión de la location apunta fuera de la tabla de strings
+        data[JIMAGE_HEADER_SIZE + 8 + 7] = 200;
+        let path = write_temp("modules-bad", &data);
+        let result = read_jimage(&path);
+        let _ = fs::remove_file(&path);
+        assert!(result.is_err());
+
+        let image = JImage {
+            data: jimage_bytes(&[]),
+            big_endian: false,
+            table_length: 1,
+            locations_size: 13,
+            strings_size: 21,
+        };
+        assert!(image.u4(image.data.len() - 2).is_err());
+        assert!(image.string(1000).is_err());
+        assert_eq!(image.string(1), Ok("java.base"));
+    }
+}
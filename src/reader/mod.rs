@@ -1,2 +1,4 @@
+pub mod class_index;
 #[allow(dead_code)]
 pub mod jar_reader;
+pub mod jdk_reader;
@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 
 use crate::errors::{KujavError, KujavResult};
@@ -9,6 +9,9 @@ pub struct KujavToml {
     pub dependencies: BTreeMap<String, String>,
     pub java: JavaConfig,
     pub minecraft: Option<MinecraftConfig>,
+    pub features: BTreeMap<String, Vec<String>>, // Feature -> features it enables; `default` is special
+    pub profiles: BTreeSet<String>,              // debug, release and every [profile.<name>]
+    pub lints: BTreeMap<String, String>,         // Lint name -> allow | warn | deny
 }
 
 #[derive(Debug)]
@@ -22,13 +25,14 @@ pub struct Package {
 #[derive(Debug, Default)]
 pub struct JavaConfig {
     pub classpath: Vec<String>,
+    pub release: Option<u32>,
 }
 
 #[derive(Debug)]
 pub struct MinecraftConfig {
     pub plugin_name: String,
     pub plugin_version: String,
-    pub main_class: String,
+    pub main_class: String, // Empty: the compiled class (package + package.name)
     pub api: String,
 }
 
@@ -53,6 +57,9 @@ impl KujavToml {
             api: String::new(),
         };
         let mut saw_minecraft = false;
+        let mut features = BTreeMap::new();
+        let mut profiles: BTreeSet<String> = ["debug", "release"].map(String::from).into();
+        let mut lints = BTreeMap::new();
 
         for raw_line in content.lines() {
             let line = raw_line.trim();
@@ -64,6 +71,9 @@ impl KujavToml {
                 if section == "minecraft" {
                     saw_minecraft = true;
                 }
+                if let Some(profile) = section.strip_prefix("profile.") {
+                    profiles.insert(profile.to_string());
+                }
                 continue;
             }
             let (k, v) = line
@@ -86,10 +96,23 @@ impl KujavToml {
                 "dependencies" => {
                     dependencies.insert(key.to_string(), trim_quoted(raw_value));
                 }
-                "java" => {
-                    if key == "classpath" {
-                        java.classpath = parse_toml_string_array(raw_value)?;
+                "java" => match key {
+                    "classpath" => java.classpath = parse_toml_string_array(raw_value)?,
+                    "release" => {
+                        let value = trim_quoted(raw_value);
+                        java.release = Some(value.parse().map_err(|_| {
+                            KujavError::toml(format!(
+                                "[java] release must be a number, found: {value}"
+                            ))
+                        })?);
                     }
+                    _ => {}
+                },
+                "lints" => {
+                    lints.insert(key.to_string(), trim_quoted(raw_value));
+                }
+                "features" => {
+                    features.insert(key.to_string(), parse_toml_string_array(raw_value)?);
                 }
                 "minecraft" => {
                     let value = trim_quoted(raw_value);
@@ -116,9 +139,9 @@ impl KujavToml {
             if minecraft.plugin_version.is_empty() {
                 minecraft.plugin_version = package.version.clone();
             }
-            if minecraft.main_class.is_empty() || minecraft.api.is_empty() {
+            if minecraft.api.is_empty() {
                 return Err(KujavError::toml(
-                    "[minecraft] requires main_class and api (paper|spigot api version string)",
+                    "[minecraft] requires api (paper|spigot api version string)",
                 ));
             }
             Some(minecraft)
@@ -126,13 +149,50 @@ impl KujavToml {
             None
         };
 
+        for (feature, enables) in &features {
+            if let Some(unknown) = enables.iter().find(|f| !features.contains_key(*f)) {
+                return Err(KujavError::toml(format!(
+                    "feature '{feature}' enables unknown feature '{unknown}'"
+                )));
+            }
+        }
+
         Ok(Self {
             package,
             dependencies,
             java,
             minecraft,
+            features,
+            profiles,
+            lints,
         })
     }
+
+    /// Active feature set: `default` (unless disabled) plus the requested ones,
+    /// closed over the features each of them enables.
+    pub fn resolve_features(
+        &self,
+        requested: &[String],
+        use_default: bool,
+    ) -> KujavResult<BTreeSet<String>> {
+        let mut pending: Vec<String> = requested.to_vec();
+        if use_default && let Some(default) = self.features.get("default") {
+            pending.extend(default.iter().cloned());
+        }
+        let mut active = BTreeSet::new();
+        while let Some(feature) = pending.pop() {
+            let enables = self.features.get(&feature).ok_or_else(|| {
+                KujavError::toml(format!(
+                    "unknown feature '{feature}' (declare it in [features])"
+                ))
+            })?;
+            if active.insert(feature) {
+                pending.extend(enables.iter().cloned());
+            }
+        }
+        active.remove("default");
+        Ok(active)
+    }
 }
 
 fn trim_quoted(value: &str) -> String {